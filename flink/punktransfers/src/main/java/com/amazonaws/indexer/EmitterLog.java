@@ -8,6 +8,7 @@ public class EmitterLog {
     public long block_number;
     public long transaction_index;
     public long log_index;
+    public long transaction_log_index;
     public String transaction_hash;
     public String address;
     public String topic0;
@@ -17,12 +18,13 @@ public class EmitterLog {
     public String data;
     public long chain_id;
     public String block_hash;
+    public boolean removed;
 
 @Override
 public String toString() {
     return String.format("EmitterLog[block=%d, txIdx=%d, logIdx=%d, txHash=%s, addr=%s, " +
-            "topics=[%s,%s,%s,%s], data=%s, chainId=%d, blockHash=%s]",
+            "topics=[%s,%s,%s,%s], data=%s, chainId=%d, blockHash=%s, removed=%b]",
         block_number, transaction_index, log_index, transaction_hash, address,
-        topic0, topic1, topic2, topic3, data, chain_id, block_hash);
+        topic0, topic1, topic2, topic3, data, chain_id, block_hash, removed);
 }
 }
\ No newline at end of file
@@ -12,11 +12,13 @@ public class PunkTransfer {
     public String to;
     public long blockNumber;
     public String transactionHash;
+    // true if the transfer was retracted by a reorg and has to be undone
+    public boolean removed;
 
     // toString function for the class
     @Override
     public String toString() {
-        return String.format("PunkTransfer[punkIndex=%s, from=%s, to=%s, blockNumber=%d, transactionHash=%s, logIdx=%s, txIdx=%s]",
-                punkIndex, from, to, blockNumber, transactionHash, logIndex, txIndex);
+        return String.format("PunkTransfer[punkIndex=%s, from=%s, to=%s, blockNumber=%d, transactionHash=%s, logIdx=%s, txIdx=%s, removed=%b]",
+                punkIndex, from, to, blockNumber, transactionHash, logIndex, txIndex, removed);
     }
 }
@@ -68,6 +68,8 @@ public class PunkTransferProcessor {
                                 "software.amazon.msk.auth.iam.IAMClientCallbackHandler")
                         .setStartingOffsets(OffsetsInitializer.offsets(specificOffsets))
                         .setProperty("commit.offsets.on.checkpoint", "true")
+                        // skip records of aborted emitter transactions
+                        .setProperty("isolation.level", "read_committed")
                         .setValueOnlyDeserializer(new SimpleStringSchema())
                         .build();
 
@@ -97,6 +99,7 @@ public class PunkTransferProcessor {
                                     transfer.txIndex = (int) log.transaction_index;
                                     transfer.blockNumber = log.block_number;
                                     transfer.transactionHash = log.transaction_hash;
+                                    transfer.removed = log.removed;
 
                                     if (log.topic0.equals(PUNK_ASSIGN_EVENT)) {
                                         transfer.from = ZERO_ADDRESS;
@@ -122,6 +122,9 @@ export class Layer1Node extends Construct {
     const groupArn = `arn:aws:kafka:${cdk.Stack.of(this).region}:${
       cdk.Stack.of(this).account
     }:group/${this.mskCluster.clusterName}/*`;
+    const transactionalIdArn = `arn:aws:kafka:${cdk.Stack.of(this).region}:${
+      cdk.Stack.of(this).account
+    }:transactional-id/${this.mskCluster.clusterName}/*`;
 
     // IAM policies and role to write to kafka
     const kafkaProducerPolicy = new iam.Policy(this, "KafkaProducerPolicy", {
@@ -139,6 +142,7 @@ export class Layer1Node extends Construct {
             "kafka-cluster:Connect",
             "kafka-cluster:AlterCluster",
             "kafka-cluster:DescribeCluster",
+            "kafka-cluster:WriteDataIdempotently",
           ],
           resources: [this.mskCluster.clusterArn],
         }),
@@ -154,6 +158,13 @@ export class Layer1Node extends Construct {
           actions: ["kafka-cluster:AlterGroup", "kafka-cluster:DescribeGroup"],
           resources: [groupArn],
         }),
+        new iam.PolicyStatement({
+          actions: [
+            "kafka-cluster:AlterTransactionalId",
+            "kafka-cluster:DescribeTransactionalId",
+          ],
+          resources: [transactionalIdArn],
+        }),
       ],
     });
 
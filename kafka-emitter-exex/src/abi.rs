@@ -0,0 +1,493 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use std::{ collections::HashMap, path::Path, str::FromStr };
+
+use alloy::{
+    dyn_abi::{ DynSolValue, EventExt, JsonAbiExt },
+    hex,
+    json_abi::{ Event, EventParam, Function, JsonAbi },
+    primitives::{ Address, Log, Selector, B256, U256 },
+};
+use eyre::WrapErr;
+use reth_tracing::tracing::info;
+
+use crate::{ args::NumericEncoding, quantity::Quantity };
+
+// event of a log, decoded with the ABI of its contract
+#[derive(Debug)]
+pub struct DecodedEvent {
+    pub name: String,
+    // e.g. Transfer(address,address,uint256)
+    pub signature: String,
+    pub parameters: Vec<DecodedParameter>,
+}
+
+// function called by a transaction, decoded with the ABI of its contract
+#[derive(Debug)]
+pub struct DecodedCall {
+    pub name: String,
+    // e.g. transfer(address,uint256)
+    pub signature: String,
+    pub parameters: Vec<DecodedParameter>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DecodedParameter {
+    // position of the parameter for unnamed parameters
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+    // events only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed: Option<bool>,
+    // events only, indexed strings, bytes, arrays and tuples are stored as the keccak256 hash of
+    // their encoding, `value` is then that hash and not the value itself
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hashed: Option<bool>,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Default)]
+struct ContractAbi {
+    events: HashMap<B256, Event>,
+    functions: HashMap<Selector, Function>,
+}
+
+impl ContractAbi {
+    fn new(abi: &JsonAbi) -> Self {
+        Self {
+            // anonymous events have no selector in topic0
+            events: abi
+                .events()
+                .filter(|event| !event.anonymous)
+                .map(|event| (event.selector(), event.clone()))
+                .collect(),
+            functions: abi
+                .functions()
+                .map(|function| (function.selector(), function.clone()))
+                .collect(),
+        }
+    }
+}
+
+// Events and functions of contracts, read from a directory of ABI files named <address>.json. The
+// files hold the ABI itself or a build artifact with an "abi" field (Hardhat, Foundry).
+#[derive(Debug, Default)]
+pub struct AbiRegistry {
+    contracts: HashMap<Address, ContractAbi>,
+}
+
+impl AbiRegistry {
+    pub fn load(dir: &Path) -> eyre::Result<Self> {
+        let mut contracts = HashMap::new();
+
+        let entries = std::fs::read_dir(dir).wrap_err_with(|| format!("❌ Can't read ABI directory {}", dir.display()))?;
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+                continue;
+            }
+
+            let address = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| Address::from_str(stem).ok())
+                .ok_or_else(|| eyre::eyre!("❌ ABI file name is not an address: {}", path.display()))?;
+
+            let content: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+            let abi: JsonAbi = match content.get("abi") {
+                Some(abi) => serde_json::from_value(abi.clone()),
+                None => serde_json::from_value(content),
+            }.wrap_err_with(|| format!("❌ Invalid ABI in {}", path.display()))?;
+
+            contracts.insert(address, ContractAbi::new(&abi));
+        }
+
+        info!(contracts = contracts.len(), dir = %dir.display(), "Loaded ABIs");
+
+        Ok(Self { contracts })
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.contracts.contains_key(address)
+    }
+
+    // None if the event isn't part of the ABI of the contract or the log doesn't match it
+    pub fn decode(&self, log: &Log, numeric_encoding: NumericEncoding) -> Option<DecodedEvent> {
+        let event = self.contracts.get(&log.address)?.events.get(log.topics().first()?)?;
+        let decoded = event.decode_log(&log.data).ok()?;
+
+        // the decoded values are split in indexed and non-indexed parameters
+        let mut indexed = decoded.indexed.into_iter();
+        let mut body = decoded.body.into_iter();
+
+        let parameters = event.inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| {
+                let value = match input.indexed {
+                    true => indexed.next(),
+                    false => body.next(),
+                }?;
+
+                Some(DecodedParameter {
+                    name: parameter_name(index, &input.name),
+                    param_type: input.ty.clone(),
+                    indexed: Some(input.indexed),
+                    hashed: Some(input.indexed && is_reference_type(input)),
+                    value: to_json(&value, numeric_encoding),
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(DecodedEvent {
+            name: event.name.clone(),
+            signature: event.signature(),
+            parameters,
+        })
+    }
+
+    // None if the selector isn't part of the ABI of the contract or the input doesn't match it
+    pub fn decode_call(&self, to: &Address, input: &[u8], numeric_encoding: NumericEncoding) -> Option<DecodedCall> {
+        let selector = Selector::try_from(input.get(..4)?).ok()?;
+        let function = self.contracts.get(to)?.functions.get(&selector)?;
+        let values = function.abi_decode_input(&input[4..]).ok()?;
+
+        let parameters = function.inputs
+            .iter()
+            .zip(values.iter())
+            .enumerate()
+            .map(|(index, (input, value))| DecodedParameter {
+                name: parameter_name(index, &input.name),
+                param_type: input.ty.clone(),
+                indexed: None,
+                hashed: None,
+                value: to_json(value, numeric_encoding),
+            })
+            .collect();
+
+        Some(DecodedCall {
+            name: function.name.clone(),
+            signature: function.signature(),
+            parameters,
+        })
+    }
+}
+
+fn parameter_name(index: usize, name: &str) -> String {
+    match name.is_empty() {
+        true => index.to_string(),
+        false => name.to_string(),
+    }
+}
+
+// types that don't fit in a topic, e.g. string, bytes, uint256[2] or tuple[]
+fn is_reference_type(param: &EventParam) -> bool {
+    param.ty == "string" || param.ty == "bytes" || param.ty.ends_with(']') || param.ty.starts_with("tuple")
+}
+
+// numbers use the same lossless encoding as the other datasets
+fn to_json(value: &DynSolValue, numeric_encoding: NumericEncoding) -> serde_json::Value {
+    let quantity = |value: U256| {
+        serde_json
+            ::to_value(Quantity::new(value, numeric_encoding))
+            .expect("quantities always serialize")
+    };
+
+    match value {
+        DynSolValue::Bool(value) => serde_json::Value::Bool(*value),
+        DynSolValue::Uint(value, _) => quantity(*value),
+        // two's complement would be ambiguous, signed numbers are always decimal strings
+        DynSolValue::Int(value, _) => serde_json::Value::String(value.to_string()),
+        DynSolValue::Address(address) => serde_json::Value::String(address.to_checksum(None)),
+        DynSolValue::FixedBytes(word, size) => serde_json::Value::String(hex::encode_prefixed(&word[..*size])),
+        DynSolValue::Function(function) => serde_json::Value::String(hex::encode_prefixed(function)),
+        DynSolValue::Bytes(bytes) => serde_json::Value::String(hex::encode_prefixed(bytes)),
+        DynSolValue::String(string) => serde_json::Value::String(string.clone()),
+        // arrays and tuples
+        _ => {
+            serde_json::Value::Array(
+                value
+                    .as_fixed_seq()
+                    .or_else(|| value.as_array())
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|value| to_json(value, numeric_encoding))
+                    .collect()
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloy::primitives::{ keccak256, Bytes, I256 };
+
+    const ABI: &str = r#"[
+        {
+            "type": "event",
+            "name": "Tagged",
+            "anonymous": false,
+            "inputs": [
+                { "name": "sender", "type": "address", "indexed": true },
+                { "name": "amount", "type": "uint256", "indexed": false },
+                { "name": "tag", "type": "string", "indexed": true },
+                { "name": "", "type": "int256", "indexed": false }
+            ]
+        },
+        {
+            "type": "event",
+            "name": "Hidden",
+            "anonymous": true,
+            "inputs": [{ "name": "value", "type": "uint256", "indexed": true }]
+        },
+        {
+            "type": "function",
+            "name": "transfer",
+            "stateMutability": "nonpayable",
+            "inputs": [{ "name": "to", "type": "address" }, { "name": "amount", "type": "uint256" }],
+            "outputs": [{ "name": "", "type": "bool" }]
+        },
+        {
+            "type": "function",
+            "name": "batch",
+            "stateMutability": "nonpayable",
+            "inputs": [{ "name": "", "type": "uint256[]" }, { "name": "", "type": "bool" }],
+            "outputs": []
+        }
+    ]"#;
+
+    fn contract() -> Address {
+        Address::repeat_byte(0xaa)
+    }
+
+    fn registry() -> AbiRegistry {
+        let abi: JsonAbi = serde_json::from_str(ABI).unwrap();
+        AbiRegistry { contracts: HashMap::from([(contract(), ContractAbi::new(&abi))]) }
+    }
+
+    fn tagged_topics() -> Vec<B256> {
+        vec![keccak256("Tagged(address,uint256,string,int256)"), Address::repeat_byte(0x01).into_word(), keccak256("tag")]
+    }
+
+    fn tagged(address: Address, topics: Vec<B256>) -> Log {
+        let data = [U256::from(1_000).to_be_bytes::<32>(), I256::MINUS_ONE.to_be_bytes::<32>()].concat();
+        Log::new_unchecked(address, topics, Bytes::from(data))
+    }
+
+    #[test]
+    fn decodes_parameters_in_event_order() {
+        let event = registry().decode(&tagged(contract(), tagged_topics()), NumericEncoding::Decimal).unwrap();
+
+        assert_eq!(event.name, "Tagged");
+        assert_eq!(event.signature, "Tagged(address,uint256,string,int256)");
+        assert_eq!(
+            serde_json::to_value(&event.parameters).unwrap(),
+            serde_json::json!([
+                {
+                    "name": "sender",
+                    "type": "address",
+                    "indexed": true,
+                    "hashed": false,
+                    "value": Address::repeat_byte(0x01).to_checksum(None),
+                },
+                { "name": "amount", "type": "uint256", "indexed": false, "hashed": false, "value": "1000" },
+                {
+                    "name": "tag",
+                    "type": "string",
+                    "indexed": true,
+                    "hashed": true,
+                    "value": hex::encode_prefixed(keccak256("tag")),
+                },
+                { "name": "3", "type": "int256", "indexed": false, "hashed": false, "value": "-1" },
+            ])
+        );
+    }
+
+    #[test]
+    fn anonymous_events_are_not_decoded() {
+        let log = Log::new_unchecked(
+            contract(),
+            vec![keccak256("Hidden(uint256)"), U256::from(1).into()],
+            Bytes::new()
+        );
+
+        assert!(registry().decode(&log, NumericEncoding::Decimal).is_none());
+    }
+
+    #[test]
+    fn logs_that_do_not_match_the_event() {
+        let registry = registry();
+
+        // topic missing, topic too many
+        let mut topics = tagged_topics();
+        topics.pop();
+        assert!(registry.decode(&tagged(contract(), topics), NumericEncoding::Decimal).is_none());
+
+        let mut topics = tagged_topics();
+        topics.push(B256::ZERO);
+        assert!(registry.decode(&tagged(contract(), topics), NumericEncoding::Decimal).is_none());
+
+        // unknown contract, unknown event
+        let log = tagged(Address::repeat_byte(0xbb), tagged_topics());
+        assert!(registry.decode(&log, NumericEncoding::Decimal).is_none());
+
+        let mut topics = tagged_topics();
+        topics[0] = keccak256("Other()");
+        assert!(registry.decode(&tagged(contract(), topics), NumericEncoding::Decimal).is_none());
+    }
+
+    fn abi_dir(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("abi-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            std::fs::write(dir.join(file), content).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn loads_abis_and_artifacts() {
+        let artifact = format!(r#"{{ "contractName": "Tagged", "abi": {}, "bytecode": "0x" }}"#, ABI);
+        let dir = abi_dir("load", &[
+            (&format!("{}.json", Address::repeat_byte(0x01)), ABI),
+            (&format!("{}.json", Address::repeat_byte(0x02)), &artifact),
+            ("README.md", "not an ABI"),
+        ]);
+
+        let registry = AbiRegistry::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let registry = registry.unwrap();
+        assert_eq!(registry.contracts.len(), 2);
+        for address in [Address::repeat_byte(0x01), Address::repeat_byte(0x02)] {
+            assert!(registry.contains(&address));
+            assert_eq!(registry.contracts[&address].events.len(), 1);
+        }
+    }
+
+    #[test]
+    fn rejects_file_names_that_are_not_addresses() {
+        let dir = abi_dir("name", &[("token.json", ABI)]);
+
+        let registry = AbiRegistry::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(registry.is_err());
+    }
+
+    #[test]
+    fn numbers() {
+        let value = DynSolValue::Uint(U256::from(1_000_000_000u64), 256);
+        assert_eq!(to_json(&value, NumericEncoding::Decimal), serde_json::json!("1000000000"));
+        assert_eq!(to_json(&value, NumericEncoding::Hex), serde_json::json!("0x3b9aca00"));
+
+        // signed numbers ignore the encoding
+        let value = DynSolValue::Int(I256::try_from(-1_000_000_000i64).unwrap(), 256);
+        assert_eq!(to_json(&value, NumericEncoding::Decimal), serde_json::json!("-1000000000"));
+        assert_eq!(to_json(&value, NumericEncoding::Hex), serde_json::json!("-1000000000"));
+        assert_eq!(
+            to_json(&DynSolValue::Int(I256::MIN, 256), NumericEncoding::Hex),
+            serde_json::json!(I256::MIN.to_string())
+        );
+    }
+
+    #[test]
+    fn bytes() {
+        let value = DynSolValue::FixedBytes(B256::right_padding_from(&[0x12, 0x34]), 2);
+        assert_eq!(to_json(&value, NumericEncoding::Decimal), serde_json::json!("0x1234"));
+        assert_eq!(
+            to_json(&DynSolValue::Bytes(vec![0xde, 0xad]), NumericEncoding::Decimal),
+            serde_json::json!("0xdead")
+        );
+    }
+
+    #[test]
+    fn arrays_and_tuples() {
+        let numbers = vec![DynSolValue::Uint(U256::from(1), 256), DynSolValue::Uint(U256::from(255), 256)];
+        let tuple = DynSolValue::Tuple(vec![
+            DynSolValue::Address(Address::repeat_byte(0x01)),
+            DynSolValue::Bool(true),
+            DynSolValue::Array(numbers.clone()),
+        ]);
+
+        assert_eq!(
+            to_json(&DynSolValue::Array(numbers.clone()), NumericEncoding::Decimal),
+            serde_json::json!(["1", "255"])
+        );
+        assert_eq!(
+            to_json(&DynSolValue::FixedArray(numbers), NumericEncoding::Hex),
+            serde_json::json!(["0x1", "0xff"])
+        );
+        assert_eq!(
+            to_json(&DynSolValue::Array(vec![tuple]), NumericEncoding::Hex),
+            serde_json::json!([[Address::repeat_byte(0x01).to_checksum(None), true, ["0x1", "0xff"]]])
+        );
+    }
+
+    fn input(name: &str, values: &[DynSolValue]) -> Vec<u8> {
+        let abi: JsonAbi = serde_json::from_str(ABI).unwrap();
+        abi.function(name).unwrap()[0].abi_encode_input(values).unwrap()
+    }
+
+    fn transfer() -> Vec<u8> {
+        input("transfer", &[DynSolValue::Address(Address::repeat_byte(0x02)), DynSolValue::Uint(U256::from(1_000), 256)])
+    }
+
+    #[test]
+    fn decodes_call() {
+        let call = registry().decode_call(&contract(), &transfer(), NumericEncoding::Hex).unwrap();
+
+        assert_eq!(call.name, "transfer");
+        assert_eq!(call.signature, "transfer(address,uint256)");
+        assert_eq!(
+            serde_json::to_value(&call.parameters).unwrap(),
+            serde_json::json!([
+                { "name": "to", "type": "address", "value": Address::repeat_byte(0x02).to_checksum(None) },
+                { "name": "amount", "type": "uint256", "value": "0x3e8" },
+            ])
+        );
+    }
+
+    #[test]
+    fn unnamed_call_parameters() {
+        let numbers = DynSolValue::Array(vec![DynSolValue::Uint(U256::from(1), 256)]);
+        let input = input("batch", &[numbers, DynSolValue::Bool(false)]);
+        let call = registry().decode_call(&contract(), &input, NumericEncoding::Decimal).unwrap();
+
+        assert_eq!(call.signature, "batch(uint256[],bool)");
+        assert_eq!(
+            serde_json::to_value(&call.parameters).unwrap(),
+            serde_json::json!([
+                { "name": "0", "type": "uint256[]", "value": ["1"] },
+                { "name": "1", "type": "bool", "value": false },
+            ])
+        );
+    }
+
+    #[test]
+    fn calls_that_do_not_match_a_function() {
+        let registry = registry();
+        let transfer = transfer();
+
+        // unknown contract, unknown selector
+        assert!(registry.decode_call(&Address::repeat_byte(0xbb), &transfer, NumericEncoding::Decimal).is_none());
+        let mut unknown = transfer.clone();
+        unknown[0] ^= 0xff;
+        assert!(registry.decode_call(&contract(), &unknown, NumericEncoding::Decimal).is_none());
+
+        // shorter than a selector, plain ETH transfer
+        assert!(registry.decode_call(&contract(), &transfer[..3], NumericEncoding::Decimal).is_none());
+        assert!(registry.decode_call(&contract(), &[], NumericEncoding::Decimal).is_none());
+
+        // arguments truncated
+        assert!(registry.decode_call(&contract(), &transfer[..transfer.len() - 1], NumericEncoding::Decimal).is_none());
+        assert!(registry.decode_call(&contract(), &transfer[..4], NumericEncoding::Decimal).is_none());
+    }
+}
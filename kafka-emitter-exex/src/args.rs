@@ -0,0 +1,99 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use std::{ path::PathBuf, sync::Arc };
+
+use clap::{ Parser, ValueEnum };
+
+use crate::{ abi::AbiRegistry, transform::TransformOptions };
+
+// arguments shared by all emitters
+#[derive(Debug, Parser)]
+pub struct ExExArgs {
+    // topic prefix for this exex
+    #[arg(long)]
+    pub exex_topic_prefix: Option<String>,
+
+    // first block to emit, default: continue where the exex left off
+    #[arg(long)]
+    pub exex_start_block: Option<u64>,
+
+    // datasets to publish, comma-separated, default: the datasets whose topics infra creates
+    #[arg(long, value_enum, value_delimiter = ',', default_values = ["blocks", "transactions", "logs"])]
+    pub exex_datasets: Vec<Dataset>,
+
+    // add the signature and the raw EIP-2718 encoding to transactions, increases the message size
+    #[arg(long, default_value_t = false)]
+    pub exex_signed_transactions: bool,
+
+    // encoding of U256 and u128 fields (wei amounts, fees, difficulty)
+    #[arg(long, value_enum, default_value = "decimal")]
+    pub exex_numeric_encoding: NumericEncoding,
+
+    // directory with the ABIs of contracts (<address>.json) to decode their logs and calls
+    #[arg(long)]
+    pub exex_abi_dir: Option<PathBuf>,
+}
+
+impl ExExArgs {
+    pub fn transform_options(&self) -> eyre::Result<TransformOptions> {
+        let abi_registry = match &self.exex_abi_dir {
+            Some(abi_dir) => Some(Arc::new(AbiRegistry::load(abi_dir)?)),
+            None => None,
+        };
+
+        // without ABIs the decoded datasets would stay empty
+        let decoded = self.exex_datasets
+            .iter()
+            .any(|dataset| matches!(dataset, Dataset::DecodedLogs | Dataset::DecodedCalls));
+        if decoded && abi_registry.is_none() {
+            return Err(eyre::eyre!("❌ The decoded-logs and decoded-calls datasets require --exex-abi-dir"));
+        }
+
+        Ok(TransformOptions {
+            signed_transactions: self.exex_signed_transactions,
+            numeric_encoding: self.exex_numeric_encoding,
+            abi_registry,
+        })
+    }
+}
+
+// datasets of the emitters, each is published to its own <prefix>-<dataset> topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Dataset {
+    Blocks,
+    Transactions,
+    Logs,
+    // opt-in, their topics have to be created before they are selected
+    Receipts,
+    Withdrawals,
+    Contracts,
+    ElRequests,
+    Authorizations,
+    Erc20Transfers,
+    Erc20Approvals,
+    NftTransfers,
+    DexSwaps,
+    AccessLists,
+    BalanceDiffs,
+    NonceDiffs,
+    StorageDiffs,
+    // only with --exex-abi-dir
+    DecodedLogs,
+    DecodedCalls,
+    // re-execute every block
+    Traces,
+    EthTransfers,
+}
+
+// encodings of U256 and u128 fields, all of them lossless
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum NumericEncoding {
+    // decimal string, e.g. "1000000000"
+    #[default]
+    Decimal,
+    // 0x-prefixed hex string, e.g. "0x3b9aca00"
+    Hex,
+}
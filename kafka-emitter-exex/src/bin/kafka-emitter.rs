@@ -3,47 +3,73 @@
 // available at http://aws.amazon.com/agreement or other written agreement between
 // Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
 
-use std::{ env, future::Future, pin::Pin, task::{ ready, Context, Poll }, thread, time::Duration };
+
+use std::{ env, thread, time::Duration };
 
 use aws_config::Region;
 use aws_msk_iam_sasl_signer::generate_auth_token;
 use clap::Parser;
-use futures_util::{ FutureExt, TryStreamExt };
+use eyre::eyre;
 
-use kafka_exex::transform::{
-    process_block_with_receipts, EmitterBlock, EmitterLog, EmitterTransaction
+use kafka_exex::{
+    args::ExExArgs,
+    emitter::{ resolve_topic_prefix, Emitter },
+    sink::{ Flushed, Sink },
+    transform::{ EmitterCursor, Record },
 };
 use rdkafka::{
     client::OAuthToken,
-    producer::{ FutureProducer, Producer, ProducerContext },
+    consumer::{ BaseConsumer, Consumer, ConsumerContext },
+    error::KafkaError,
+    producer::{ DeliveryFuture, FutureProducer, FutureRecord, Producer, ProducerContext },
+    types::RDKafkaErrorCode,
     ClientConfig,
     ClientContext,
+    Message,
+    Offset,
+    TopicPartitionList,
 };
-use reth::{
-    api::FullNodeComponents,
-    builder::NodeTypes,
-    chainspec::{ EthChainSpec, EthereumChainSpecParser },
-    primitives::EthPrimitives,
-    providers::BlockHashReader,
-    rpc::types::BlockNumHash,
-};
-use reth_execution_types::Chain;
-use reth_exex::{ ExExContext, ExExEvent, ExExNotification };
+use reth::{ chainspec::{ EthChainSpec, EthereumChainSpecParser }, rpc::types::BlockNumHash };
 use reth_node_ethereum::EthereumNode;
-use reth_tracing::tracing::info;
+use reth_tracing::tracing::{ info, warn };
 
 use dotenv::dotenv;
 use tokio::{ runtime::Handle, time::timeout };
 
+// retry settings for records that Kafka did not accept or acknowledge
+const MAX_SEND_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+// records handed to the producer before the sink waits for their delivery, bounds the payloads
+// kept for resending and the producer queue while backfilling large segments
+const MAX_PENDING_DELIVERIES: usize = 50_000;
+
+// timeout for initializing and aborting Kafka transactions
+const TRANSACTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+// The broker aborts transactions that stay open longer. Each segment is one transaction, however
+// large, so this is the broker's default transaction.max.timeout.ms (15 minutes), the maximum it
+// accepts. Also the timeout for committing a transaction.
+const KAFKA_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(900);
+
+// number of records at the end of the cursor topic that are searched for the last cursor
+const CURSOR_LOOKBACK: i64 = 1000;
+const CURSOR_POLL_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Parser)]
-pub struct ExExArgs {
-    // topic prefix for this exex
-    #[arg(long)]
-    pub exex_topic_prefix: Option<String>,
-
-    // topic prefix for this exex
-    #[arg(long)]
-    pub exex_start_block: Option<u64>,
+pub struct KafkaExExArgs {
+    #[command(flatten)]
+    pub exex: ExExArgs,
+
+    // write each segment in one kafka transaction (exactly-once for read_committed consumers)
+    #[arg(long, default_value_t = false)]
+    pub exex_kafka_transactions: bool,
+
+    // write the <prefix>-cursor topic and resume after the last block in it, --exex-start-block
+    // is only used while the topic is empty
+    #[arg(long, default_value_t = false)]
+    pub exex_resume_from_sink: bool,
 }
 
 struct IamProducerContext {
@@ -70,6 +96,9 @@ impl ProducerContext for IamProducerContext {
     }
 }
 
+// the consumer only reads the cursor topic at startup and uses the default behaviour
+impl ConsumerContext for IamProducerContext {}
+
 impl ClientContext for IamProducerContext {
     const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
 
@@ -99,108 +128,187 @@ impl ClientContext for IamProducerContext {
     }
 }
 
-struct KafkaEmitter<Node: FullNodeComponents> {
-    ctx: ExExContext<Node>,
-
-    // Kafka producer
+// publishes each dataset to the <prefix>-<dataset> topic of the MSK cluster
+struct KafkaSink {
     producer: FutureProducer<IamProducerContext>,
+    aws_region: String,
+    kafka_broker: String,
     topic_prefix: String,
+    chain_id: u64,
+    transactional: bool,
+    in_transaction: bool,
+    // records of the open transaction that the producer may still hold in its queue
+    queued: usize,
+    // retries of aborted transactions of the current notification
+    commit_attempt: u32,
+    commit_backoff: Duration,
+    // the cursor topic is only needed to resume from it, infra doesn't create it
+    write_cursor: bool,
+
+    // records of the current notification, not yet acknowledged by Kafka (only without transactions)
+    deliveries: Vec<PendingDelivery>,
 }
 
-impl<Node: FullNodeComponents> KafkaEmitter<Node> {
+impl KafkaSink {
     fn new(
-        mut ctx: ExExContext<Node>,
-        producer: FutureProducer<IamProducerContext>,
-        topic_prefix: Option<String>,
-        start_block: Option<u64>
+        aws_region: String,
+        kafka_broker: String,
+        topic_prefix: String,
+        chain_id: u64,
+        transactional: bool,
+        write_cursor: bool
     ) -> Self {
-        match start_block {
-            None => {
-                // do not reset start block, continue onwards
-                info!("Exex start where it left off");
-            }
-            Some(0) => {
-                // reset the exex to start from the genesis block again
-                // FIXME: This would start from block 1, can't define a blocknumhash before genesis.
-                let gen_hash = ctx.config.chain.genesis_hash();
-                let genesis_block_num_hash = BlockNumHash { number: 0, hash: gen_hash };
-                ctx.set_notifications_with_head(reth_exex::ExExHead {
-                    block: genesis_block_num_hash,
-                });
-                info!(start_block=?genesis_block_num_hash, "Reset exex to start after");
-            }
-            Some(start_block) => {
-                // set the start block
-                let block_prior_to_start_block = start_block - 1;
-                let start_block_num_hash = BlockNumHash {
-                    number: block_prior_to_start_block,
-                    hash: ctx.provider().block_hash(block_prior_to_start_block).unwrap().unwrap(),
-                };
-                ctx.set_notifications_with_head(reth_exex::ExExHead {
-                    block: start_block_num_hash,
-                });
-                info!(start_block=?start_block_num_hash, "Reset exex to start after");
-            }
-        }
+        // stable across restarts, so a restarted emitter fences its zombie predecessor
+        let transactional_id = transactional
+            .then(|| format!("{}-{}-kafka-emitter", topic_prefix, chain_id));
 
-        let prefix = match topic_prefix {
-            None => {
-                // use default value <chain id>-<chain name>
-                let chain_id = ctx.config.chain.chain_id();
-                let chain_name = match ctx.config.chain.chain().named() {
-                    Some(chain_name) => chain_name.to_string(),
-                    None => chain_id.to_string(),
-                };
-                chain_name
-            }
-            Some(prefix) => {
-                // set the topic prefix
-                prefix
-            }
-        };
+        let producer = create_producer(aws_region.clone(), kafka_broker.clone(), transactional_id);
 
-        info!(topic_prefix=?prefix, "Using");
+        info!("✅ Created producer");
 
         Self {
-            ctx,
             producer,
-            topic_prefix: prefix,
+            aws_region,
+            kafka_broker,
+            topic_prefix,
+            chain_id,
+            transactional,
+            in_transaction: false,
+            queued: 0,
+            commit_attempt: 1,
+            commit_backoff: INITIAL_BACKOFF,
+            write_cursor,
+            deliveries: Vec::new(),
         }
     }
-}
 
-impl<Node: FullNodeComponents<Types: NodeTypes<Primitives = EthPrimitives>>> Future
-for KafkaEmitter<Node> {
-    type Output = eyre::Result<()>;
+    fn send<R: Record>(&mut self, record: &R) -> eyre::Result<()> {
+        if self.transactional && !self.in_transaction {
+            self.producer
+                .begin_transaction()
+                .map_err(|err| eyre!("❌ Failed to begin transaction: {}", err))?;
+            self.in_transaction = true;
+        }
+
+        let topic = format!("{}-{}", self.topic_prefix, R::DATASET);
+        let key = record.key();
+        let payload = serde_json::to_string(record)?;
+
+        let delivery = match self.producer.send_result(FutureRecord::to(&topic).key(&key).payload(&payload)) {
+            Ok(delivery) => delivery,
+            // the local queue is full, back off without stalling the other tasks of the worker
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                tokio::task::block_in_place(|| {
+                    Handle::current().block_on(enqueue_record(&self.producer, &topic, &key, &payload))
+                })?
+            }
+            Err((err, _)) => {
+                return Err(eyre!("❌ Failed to enqueue record {} for {}: {}", key, topic, err));
+            }
+        };
+
+        // The records of a transaction are not kept for resending, the emitter publishes them again
+        // from the chain when the transaction aborts. Only wait for the producer to deliver them
+        // in chunks, so its queue doesn't fill up. They stay invisible until the commit.
+        if self.transactional {
+            drop(delivery);
+            self.queued += 1;
+            if self.queued >= MAX_PENDING_DELIVERIES {
+                tokio::task::block_in_place(|| self.producer.flush(KAFKA_TRANSACTION_TIMEOUT))
+                    .map_err(|err| eyre!("❌ Failed to deliver records of the transaction: {}", err))?;
+                self.queued = 0;
+            }
+            return Ok(());
+        }
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
+        self.deliveries.push(PendingDelivery { topic, key, payload, delivery });
 
-        while let Some(notification) = ready!(this.ctx.notifications.try_next().poll_unpin(cx))? {
-            match &notification {
-                ExExNotification::ChainCommitted { new } => {
-                    info!(blocks = ?new.range(), "Received segment ");
+        if self.deliveries.len() >= MAX_PENDING_DELIVERIES {
+            wait_for_deliveries(&self.producer, std::mem::take(&mut self.deliveries))?;
+        }
 
-                    // call process_committed_chain(new)
-                    let producer = this.producer.clone();
-                    let topic: &str = this.topic_prefix.as_ref();
-                    let chain_id = this.ctx.config.chain.chain_id();
-                    process_committed_chain(new, &producer, topic, chain_id);
-                }
-                ExExNotification::ChainReorged { old, new } => {
-                    info!(from_chain = ?old.range(), to_chain = ?new.range(), "Received reorg");
+        Ok(())
+    }
+
+    // Commits all records of a notification in one transaction, so read_committed consumers never
+    // see half a segment. An aborted transaction is discarded and the emitter publishes the
+    // notification again, up to MAX_SEND_ATTEMPTS times with a bounded backoff.
+    fn commit_transaction(&mut self) -> eyre::Result<Flushed> {
+        tokio::task::block_in_place(|| {
+            let result = self.producer.commit_transaction(KAFKA_TRANSACTION_TIMEOUT);
+            self.in_transaction = false;
+            self.queued = 0;
+
+            match result {
+                Ok(()) => {
+                    self.commit_attempt = 1;
+                    self.commit_backoff = INITIAL_BACKOFF;
+                    Ok(Flushed::Delivered)
                 }
-                ExExNotification::ChainReverted { old } => {
-                    info!(reverted_chain = ?old.range(), "Received revert");
+                Err(err) if err.txn_requires_abort() && self.commit_attempt < MAX_SEND_ATTEMPTS => {
+                    warn!(attempt=self.commit_attempt, err=%err, "Transaction failed, publishing the segment again");
+                    self.producer
+                        .abort_transaction(TRANSACTION_TIMEOUT)
+                        .map_err(|err| eyre!("❌ Failed to abort transaction: {}", err))?;
+                    thread::sleep(self.commit_backoff);
+                    self.commit_backoff = (self.commit_backoff * 2).min(MAX_BACKOFF);
+                    self.commit_attempt += 1;
+                    Ok(Flushed::Aborted)
                 }
+                Err(err) => Err(eyre!("❌ Failed to commit transaction: {}", err)),
             }
+        })
+    }
+}
+
+impl Sink for KafkaSink {
+    fn publish_record<R: Record>(&mut self, record: &R) -> eyre::Result<()> {
+        self.send(record)
+    }
+
+    fn retract<R: Record>(&mut self, record: &R) -> eyre::Result<()> {
+        // same key and topic as the original record
+        self.send(record)
+    }
 
-            if let Some(committed_chain) = notification.committed_chain() {
-                // update exex to new height
-                this.ctx.events.send(ExExEvent::FinishedHeight(committed_chain.tip().num_hash()))?;
+    fn flush(&mut self, head: BlockNumHash) -> eyre::Result<Flushed> {
+        let emitter_cursor = EmitterCursor {
+            chain_id: self.chain_id,
+            block_number: head.number,
+            block_hash: head.hash,
+        };
+
+        if self.transactional {
+            if self.write_cursor {
+                self.send(&emitter_cursor)?;
+            }
+            // nothing was published in this notification
+            if !self.in_transaction {
+                return Ok(Flushed::Delivered);
             }
+            return self.commit_transaction();
         }
-        Poll::Ready(Ok(()))
+
+        wait_for_deliveries(&self.producer, std::mem::take(&mut self.deliveries))?;
+
+        // the cursor is written last, so it never points beyond delivered records
+        if self.write_cursor {
+            self.send(&emitter_cursor)?;
+            wait_for_deliveries(&self.producer, std::mem::take(&mut self.deliveries))?;
+        }
+
+        Ok(Flushed::Delivered)
+    }
+
+    fn last_cursor(&mut self) -> eyre::Result<Option<BlockNumHash>> {
+        let cursor = read_cursor(
+            self.aws_region.clone(),
+            self.kafka_broker.clone(),
+            &self.topic_prefix,
+            self.chain_id
+        )?;
+
+        Ok(cursor.map(|cursor| BlockNumHash { number: cursor.block_number, hash: cursor.block_hash }))
     }
 }
 
@@ -212,20 +320,42 @@ fn read_env_vars() -> (String, String) {
     (aws_region, kafka_broker)
 }
 
-fn create_producer(aws_region: String, kafka_broker: String) -> FutureProducer<IamProducerContext> {
+fn create_producer(
+    aws_region: String,
+    kafka_broker: String,
+    transactional_id: Option<String>
+) -> FutureProducer<IamProducerContext> {
     let region = Region::new(aws_region);
 
     info!(broker=kafka_broker.clone(), region=&region.to_string(), "Creating producer");
     let context = IamProducerContext::new(region, Handle::current());
 
-
-    let producer: FutureProducer<IamProducerContext> = ClientConfig::new()
+    let mut config = ClientConfig::new();
+    config
         .set("bootstrap.servers", &kafka_broker)
         .set("security.protocol", "SASL_SSL")
         .set("sasl.mechanism", "OAUTHBEARER")
+        // keeps the order of records when librdkafka retries internally
+        .set("enable.idempotence", "true");
+
+    if let Some(transactional_id) = &transactional_id {
+        config
+            .set("transactional.id", transactional_id)
+            .set("transaction.timeout.ms", KAFKA_TRANSACTION_TIMEOUT.as_millis().to_string());
+    }
+
+    let producer: FutureProducer<IamProducerContext> = config
         .create_with_context(context)
         .expect("❌ Producer creation error");
 
+    if let Some(transactional_id) = transactional_id {
+        // fences any older producer with the same id and aborts its open transaction
+        producer
+            .init_transactions(TRANSACTION_TIMEOUT)
+            .expect("❌ Should have initialized transactions");
+        info!(transactional_id, "Initialized transactions");
+    }
+
     let metadata = producer
         .client()
         .fetch_metadata(Option::None, Duration::from_millis(2500))
@@ -239,137 +369,183 @@ fn create_producer(aws_region: String, kafka_broker: String) -> FutureProducer<I
     producer
 }
 
-fn process_committed_chain(
-    new: &Chain,
-    producer: &FutureProducer<IamProducerContext>,
+// read the last cursor of this chain from the compacted <prefix>-cursor topic
+fn read_cursor(
+    aws_region: String,
+    kafka_broker: String,
     topic_prefix: &str,
-    chain_id: u64,
-) {
-    let start_time = std::time::Instant::now();
-
-    let number_of_transactions = u64
-        ::try_from(
-            new
-                .blocks_iter()
-                .map(|block| { block.transaction_count() })
-                .fold(0, |acc, e| acc + e)
-        )
-        .unwrap_or_default();
-
-    // Clone the producer and topic to move into the future
-    let producer_clone = producer.clone();
+    chain_id: u64
+) -> eyre::Result<Option<EmitterCursor>> {
+    let topic = format!("{}-cursor", topic_prefix);
+    let context = IamProducerContext::new(Region::new(aws_region), Handle::current());
 
+    let consumer: BaseConsumer<IamProducerContext> = ClientConfig::new()
+        .set("bootstrap.servers", &kafka_broker)
+        .set("security.protocol", "SASL_SSL")
+        .set("sasl.mechanism", "OAUTHBEARER")
+        .set("enable.auto.commit", "false")
+        // ignore cursors of aborted transactions
+        .set("isolation.level", "read_committed")
+        .create_with_context(context)
+        .map_err(|err| eyre!("❌ Consumer creation error: {}", err))?;
 
-    // process blocks
-    new.blocks_and_receipts().for_each(|(block, receipts)| {
-        let (emitter_block, emitter_transactions) =  process_block_with_receipts(block, receipts, chain_id);
+    let (low, high) = match consumer.fetch_watermarks(&topic, 0, CURSOR_POLL_TIMEOUT) {
+        Ok(watermarks) => watermarks,
+        Err(err) if err.rdkafka_error_code() == Some(RDKafkaErrorCode::UnknownTopicOrPartition) => {
+            warn!(topic, "Cursor topic does not exist");
+            return Ok(None);
+        }
+        Err(err) => {
+            return Err(eyre!("❌ Failed to fetch watermarks of {}: {}", topic, err));
+        }
+    };
 
-        send_block_to_kafka(&producer_clone, topic_prefix, emitter_block);
+    if high <= low {
+        return Ok(None);
+    }
 
-        emitter_transactions.iter().for_each(|(emitter_transaction, emitter_logs)| {
-            send_transaction_to_kafka(&producer_clone, topic_prefix, emitter_transaction);
+    let mut assignment = TopicPartitionList::new();
+    assignment.add_partition_offset(&topic, 0, Offset::Offset((high - CURSOR_LOOKBACK).max(low)))?;
+    consumer.assign(&assignment)?;
+
+    // keep the last cursor of this chain, transaction markers at the end are skipped by the consumer
+    let key = chain_id.to_string();
+    let mut cursor = None;
+    while let Some(message) = consumer.poll(CURSOR_POLL_TIMEOUT) {
+        let message = message?;
+        if message.key() == Some(key.as_bytes()) {
+            if let Some(payload) = message.payload() {
+                cursor = Some(serde_json::from_slice::<EmitterCursor>(payload)?);
+            }
+        }
+        if message.offset() >= high - 1 {
+            break;
+        }
+    }
 
-            emitter_logs.iter().for_each(|emitter_log| {
-                send_log_to_kafka(&producer_clone, topic_prefix, emitter_log);
-            })
-        });
+    info!(topic, cursor=?cursor, "Read cursor");
 
-    });
+    Ok(cursor)
+}
 
-    log_segment_processed(new, start_time, number_of_transactions);
+// a record that was handed to the producer, but not yet acknowledged by Kafka
+struct PendingDelivery {
+    topic: String,
+    key: String,
+    payload: String,
+    delivery: DeliveryFuture,
 }
 
+async fn enqueue_record(
+    producer: &FutureProducer<IamProducerContext>,
+    topic: &str,
+    key: &str,
+    payload: &str
+) -> eyre::Result<DeliveryFuture> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        let record = FutureRecord::to(topic).key(key).payload(payload);
+        match producer.send_result(record) {
+            Ok(delivery) => {
+                return Ok(delivery);
+            }
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) if attempt < MAX_SEND_ATTEMPTS => {
+                // the local queue is full, give the producer some time to drain it
+                warn!(topic, key, attempt, "Producer queue full, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+            Err((err, _)) => {
+                return Err(eyre!("❌ Failed to enqueue record {} for {}: {}", key, topic, err));
+            }
+        }
+    }
+}
 
+// Blocks until Kafka acknowledged all records. After a failed delivery, the failed record and all
+// records queued after it are resent in their original order, so a retry never moves a record
+// behind a later record with the same key (e.g. a retraction behind its replacement).
+fn wait_for_deliveries(
+    producer: &FutureProducer<IamProducerContext>,
+    mut deliveries: Vec<PendingDelivery>
+) -> eyre::Result<()> {
+    tokio::task::block_in_place(|| {
+        Handle::current().block_on(async {
+            let mut backoff = INITIAL_BACKOFF;
+            let mut attempt = 1;
+            let mut start = 0;
+            loop {
+                // wait for every record, so nothing is in flight anymore when resending
+                let mut failed = None;
+                for (index, pending) in deliveries.iter_mut().enumerate().skip(start) {
+                    let err = match (&mut pending.delivery).await {
+                        Ok(Ok(_)) => continue,
+                        Ok(Err((err, _))) => err.to_string(),
+                        Err(_) => "delivery canceled".to_string(),
+                    };
+                    failed.get_or_insert((index, err));
+                }
 
-fn log_segment_processed(new: &Chain, start_time: std::time::Instant, number_of_transactions: u64) {
-    let processed_blocks = new.tip().number - new.first().number + 1;
-    let blocks_per_second = (processed_blocks as f64) / start_time.elapsed().as_secs_f64();
-    let tx_per_second = (number_of_transactions as f64) / start_time.elapsed().as_secs_f64();
-    info!(
-        blocks = ?new.range(),
-        processed_blocks,
-        blocks_per_second,
-        transactions=number_of_transactions,
-        tx_per_second,
-        "Processed segment"
-    )
-}
+                let Some((index, err)) = failed else {
+                    return Ok(());
+                };
 
-fn send_transaction_to_kafka(
-    producer_clone: &FutureProducer<IamProducerContext>,
-    topic_prefix: &str,
-    emitter_transaction: &EmitterTransaction
-) {
-    let topic = format!("{}-transactions", topic_prefix);
-    let key = format!(
-        "{}-{}",
-        emitter_transaction.block_number,
-        emitter_transaction.transaction_index
-    );
-    let payload = serde_json::to_string(emitter_transaction).unwrap();
-    let record = rdkafka::producer::future_producer::FutureRecord
-        ::to(&topic)
-        .key(&key)
-        .payload(&payload);
-    let _ = producer_clone.send_result(record);
-}
+                let PendingDelivery { topic, key, .. } = &deliveries[index];
+                if attempt >= MAX_SEND_ATTEMPTS {
+                    return Err(
+                        eyre!("❌ Failed to deliver record {} to {} after {} attempts: {}", key, topic, attempt, err)
+                    );
+                }
 
-fn send_log_to_kafka(
-    producer_clone: &FutureProducer<IamProducerContext>,
-    topic_prefix: &str,
-    emitter_log: &EmitterLog
-) {
-    let topic = format!("{}-logs", topic_prefix);
-    let key = format!(
-        "{}-{}-{}",
-        emitter_log.block_number,
-        emitter_log.transaction_index,
-        emitter_log.log_index
-    );
-    let payload = serde_json::to_string(emitter_log).unwrap();
-    let record = rdkafka::producer::future_producer::FutureRecord
-        ::to(&topic)
-        .key(&key)
-        .payload(&payload);
-    let _ = producer_clone.send_result(record);
-}
+                warn!(topic, key, attempt, err, resent=deliveries.len() - index, "Delivery failed, resending");
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
 
-fn send_block_to_kafka(
-    producer_clone: &FutureProducer<IamProducerContext>,
-    topic_prefix: &str,
-    emitter_block: EmitterBlock
-) {
-    let topic = format!("{}-blocks", topic_prefix);
-    // let key = emitter_block.block_number.to_string();
-    let payload = serde_json::to_string(&emitter_block).unwrap();
-    let record = rdkafka::producer::future_producer::FutureRecord
-        ::to(&topic)
-        // .key(&key)
-        .key("")
-        .payload(&payload);
-    let _ = producer_clone.send_result(record);
+                for pending in deliveries[index..].iter_mut() {
+                    pending.delivery = enqueue_record(producer, &pending.topic, &pending.key, &pending.payload).await?;
+                }
+                start = index;
+            }
+        })
+    })
 }
 
 fn main() -> eyre::Result<()> {
-    reth::cli::Cli::<EthereumChainSpecParser, ExExArgs>
+    reth::cli::Cli::<EthereumChainSpecParser, KafkaExExArgs>
         ::parse()
-        .run(async move |builder, extra_args: ExExArgs| {
-            let topic_prefix = extra_args.exex_topic_prefix;
-            let start_block = extra_args.exex_start_block;
+        .run(async move |builder, extra_args: KafkaExExArgs| {
+            let topic_prefix = extra_args.exex.exex_topic_prefix;
+            let start_block = extra_args.exex.exex_start_block;
+            let options = extra_args.exex.transform_options()?;
+            let datasets = extra_args.exex.exex_datasets;
+            let transactional = extra_args.exex_kafka_transactions;
+            let resume_from_sink = extra_args.exex_resume_from_sink;
 
             // setup MSK env vars
             let (aws_region, kafka_broker) = read_env_vars();
             info!(aws_region, kafka_broker, "MSK env vars");
 
-            let producer = create_producer(aws_region, kafka_broker);
-
-            info!("✅ Created producer");
-
             let handle = builder
                 .node(EthereumNode::default())
-                .install_exex("kafka-emitter-exex", async move |ctx|
-                    Ok(KafkaEmitter::new(ctx, producer, topic_prefix, start_block))
-                )
+                .install_exex("kafka-emitter-exex", async move |ctx| {
+                    let topic_prefix = resolve_topic_prefix(&ctx, topic_prefix);
+                    let chain_id = ctx.config.chain.chain_id();
+
+                    let sink = KafkaSink::new(
+                        aws_region,
+                        kafka_broker,
+                        topic_prefix,
+                        chain_id,
+                        transactional,
+                        // the cursor is part of every transaction, so it always matches the committed records
+                        resume_from_sink || transactional
+                    );
+
+                    Emitter::new(ctx, sink, start_block, resume_from_sink, datasets, options)
+                })
                 .launch().await?;
 
             handle.wait_for_node_exit().await
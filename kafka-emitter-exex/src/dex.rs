@@ -0,0 +1,121 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use alloy::primitives::{ Log, I256, U256 };
+
+use crate::tokens::decode_event;
+
+// pair events, shared by forks like SushiSwap
+pub mod uniswap_v2 {
+    alloy::sol! {
+        event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to);
+        event Mint(address indexed sender, uint256 amount0, uint256 amount1);
+        event Burn(address indexed sender, uint256 amount0, uint256 amount1, address indexed to);
+        event Sync(uint112 reserve0, uint112 reserve1);
+    }
+}
+
+// pool events, the ticks are indexed as well
+pub mod uniswap_v3 {
+    alloy::sol! {
+        event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick);
+        event Mint(address sender, address indexed owner, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount, uint256 amount0, uint256 amount1);
+        event Burn(address indexed owner, int24 indexed tickLower, int24 indexed tickUpper, uint128 amount, uint256 amount0, uint256 amount1);
+    }
+}
+
+pub fn decode_uniswap_v2_swap(log: &Log) -> Option<uniswap_v2::Swap> {
+    decode_event(log, 3, 2)
+}
+
+pub fn decode_uniswap_v2_mint(log: &Log) -> Option<uniswap_v2::Mint> {
+    decode_event(log, 2, 1)
+}
+
+pub fn decode_uniswap_v2_burn(log: &Log) -> Option<uniswap_v2::Burn> {
+    decode_event(log, 3, 2)
+}
+
+pub fn decode_uniswap_v2_sync(log: &Log) -> Option<uniswap_v2::Sync> {
+    decode_event(log, 1, 0)
+}
+
+pub fn decode_uniswap_v3_swap(log: &Log) -> Option<uniswap_v3::Swap> {
+    decode_event(log, 3, 2)
+}
+
+// only the owner is an address topic, the ticks are sign-extended
+pub fn decode_uniswap_v3_mint(log: &Log) -> Option<uniswap_v3::Mint> {
+    decode_event(log, 4, 1)
+}
+
+pub fn decode_uniswap_v3_burn(log: &Log) -> Option<uniswap_v3::Burn> {
+    decode_event(log, 4, 1)
+}
+
+// V3 amounts are deltas of the pool balance: positive amounts go in, negative amounts go out
+pub fn split_amount(amount: I256) -> (U256, U256) {
+    match amount.is_negative() {
+        true => (U256::ZERO, amount.unsigned_abs()),
+        false => (amount.unsigned_abs(), U256::ZERO),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloy::{ primitives::{ aliases::I24, Address, LogData }, sol_types::SolEvent };
+
+    fn log(data: LogData) -> Log {
+        Log { address: Address::with_last_byte(0xaa), data }
+    }
+
+    #[test]
+    fn uniswap_v2_swap() {
+        let data = uniswap_v2::Swap {
+            sender: Address::with_last_byte(1),
+            amount0In: U256::from(10),
+            amount1In: U256::ZERO,
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(20),
+            to: Address::with_last_byte(2),
+        }.encode_log_data();
+
+        let swap = decode_uniswap_v2_swap(&log(data.clone())).unwrap();
+        assert_eq!(swap.to, Address::with_last_byte(2));
+        assert_eq!(swap.amount1Out, U256::from(20));
+
+        let mut topics = data.topics().to_vec();
+        topics[2][0] = 1;
+        assert!(decode_uniswap_v2_swap(&log(LogData::new_unchecked(topics, data.data.clone()))).is_none());
+    }
+
+    #[test]
+    fn uniswap_v3_mint_with_negative_ticks() {
+        let data = uniswap_v3::Mint {
+            sender: Address::with_last_byte(1),
+            owner: Address::with_last_byte(2),
+            tickLower: I24::try_from(-887220).unwrap(),
+            tickUpper: I24::try_from(60).unwrap(),
+            amount: 1000,
+            amount0: U256::from(5),
+            amount1: U256::from(6),
+        }.encode_log_data();
+
+        // the lower tick topic is sign-extended with 0xff bytes, it isn't an address topic
+        let mint = decode_uniswap_v3_mint(&log(data)).unwrap();
+        assert_eq!(i32::try_from(mint.tickLower).unwrap(), -887220);
+        assert_eq!(i32::try_from(mint.tickUpper).unwrap(), 60);
+        assert_eq!(mint.owner, Address::with_last_byte(2));
+    }
+
+    #[test]
+    fn v3_amounts_split_into_in_and_out() {
+        assert_eq!(split_amount(I256::try_from(5).unwrap()), (U256::from(5), U256::ZERO));
+        assert_eq!(split_amount(I256::try_from(-5).unwrap()), (U256::ZERO, U256::from(5)));
+        assert_eq!(split_amount(I256::ZERO), (U256::ZERO, U256::ZERO));
+    }
+}
@@ -0,0 +1,646 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use std::{ future::Future, pin::Pin, task::{ ready, Context, Poll } };
+
+use alloy::primitives::U256;
+use futures_util::{ FutureExt, TryStreamExt };
+use reth::{
+    api::FullNodeComponents,
+    builder::NodeTypes,
+    chainspec::{ EthChainSpec, EthereumHardforks },
+    primitives::EthPrimitives,
+    providers::{ BlockHashReader, HeaderProvider, StateProviderFactory },
+    rpc::types::BlockNumHash,
+};
+use reth_consensus_common::calc::base_block_reward;
+use reth_evm::ConfigureEvm;
+use reth_execution_types::Chain;
+use reth_exex::{ ExExContext, ExExEvent, ExExNotification };
+use reth_tracing::tracing::{ info, warn };
+
+use crate::{
+    args::Dataset,
+    sink::{ Flushed, Sink },
+    state::StateChanges,
+    trace::BlockTracer,
+    transform::{
+        process_block_with_receipts,
+        retract_block_with_receipts,
+        BlockContext,
+        EmitterBlockRecords,
+        SkippedLogs,
+        TransformOptions,
+    },
+};
+
+// ExEx that transforms the notifications of the node and publishes them to a sink
+pub struct Emitter<Node: FullNodeComponents, S: Sink> {
+    ctx: ExExContext<Node>,
+    sink: S,
+    datasets: Vec<Dataset>,
+    options: TransformOptions,
+    // only when the traces or eth-transfers dataset is selected
+    tracer: Option<BlockTracer<Node::Evm>>,
+}
+
+impl<Node: FullNodeComponents<Types: NodeTypes<Primitives = EthPrimitives>>, S: Sink> Emitter<Node, S> {
+    pub fn new(
+        mut ctx: ExExContext<Node>,
+        mut sink: S,
+        start_block: Option<u64>,
+        resume_from_sink: bool,
+        datasets: Vec<Dataset>,
+        options: TransformOptions
+    ) -> eyre::Result<Self> {
+        let cursor = if resume_from_sink { sink.last_cursor()? } else { None };
+
+        match (start_block, cursor) {
+            (_, Some(cursor)) => {
+                // resume after the last block that was fully published, the start block only
+                // applies until the sink has a cursor
+                let canonical_hash = ctx.provider().block_hash(cursor.number)?;
+                if canonical_hash != Some(cursor.hash) {
+                    // reth reverts the non-canonical blocks from its WAL before it continues
+                    warn!(cursor=?cursor, canonical_hash=?canonical_hash, "Cursor is not canonical");
+                }
+                ctx.set_notifications_with_head(reth_exex::ExExHead { block: cursor });
+                info!(start_block=?cursor, "Resume exex from cursor after");
+            }
+            (None, None) => {
+                // do not reset start block, continue onwards
+                info!("Exex start where it left off");
+            }
+            (Some(0), None) => {
+                // reset the exex to start from the genesis block again
+                // FIXME: This would start from block 1, can't define a blocknumhash before genesis.
+                let gen_hash = ctx.config.chain.genesis_hash();
+                let genesis_block_num_hash = BlockNumHash { number: 0, hash: gen_hash };
+                ctx.set_notifications_with_head(reth_exex::ExExHead {
+                    block: genesis_block_num_hash,
+                });
+                info!(start_block=?genesis_block_num_hash, "Reset exex to start after");
+            }
+            (Some(start_block), None) => {
+                // set the start block
+                let block_prior_to_start_block = start_block - 1;
+                let start_block_num_hash = BlockNumHash {
+                    number: block_prior_to_start_block,
+                    hash: ctx.provider().block_hash(block_prior_to_start_block).unwrap().unwrap(),
+                };
+                ctx.set_notifications_with_head(reth_exex::ExExHead {
+                    block: start_block_num_hash,
+                });
+                info!(start_block=?start_block_num_hash, "Reset exex to start after");
+            }
+        }
+
+        info!(datasets=?datasets, options=?options, "Publishing");
+
+        // re-execute the blocks for their traces
+        let tracing = datasets.contains(&Dataset::Traces) || datasets.contains(&Dataset::EthTransfers);
+        let tracer = tracing.then(|| BlockTracer::new(ctx.evm_config().clone()));
+
+        Ok(Self { ctx, sink, datasets, options, tracer })
+    }
+}
+
+// default topic prefix is the name of the chain, or its id for unnamed chains
+pub fn resolve_topic_prefix<Node: FullNodeComponents>(
+    ctx: &ExExContext<Node>,
+    topic_prefix: Option<String>
+) -> String {
+    let prefix = match topic_prefix {
+        None => {
+            let chain_id = ctx.config.chain.chain_id();
+            let chain_name = match ctx.config.chain.chain().named() {
+                Some(chain_name) => chain_name.to_string(),
+                None => chain_id.to_string(),
+            };
+            chain_name
+        }
+        Some(prefix) => {
+            // set the topic prefix
+            prefix
+        }
+    };
+
+    info!(topic_prefix=?prefix, "Using");
+
+    prefix
+}
+
+impl<Node: FullNodeComponents<Types: NodeTypes<Primitives = EthPrimitives>>, S: Sink + Unpin> Future
+for Emitter<Node, S> {
+    type Output = eyre::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while let Some(notification) = ready!(this.ctx.notifications.try_next().poll_unpin(cx))? {
+            let chain_spec = this.ctx.config.chain.as_ref();
+            let provider = this.ctx.provider();
+
+            // all records of a notification are flushed together, an aborted flush discarded them
+            // all, so the whole notification is published again
+            loop {
+                publish_notification(
+                    &notification,
+                    &mut this.sink,
+                    provider,
+                    chain_spec,
+                    &this.datasets,
+                    &this.options,
+                    this.tracer.as_ref()
+                )?;
+
+                // only report the height once the sink delivered every record, otherwise reth
+                // could prune its WAL while records are still in flight
+                match this.sink.flush(notification_head(&notification))? {
+                    Flushed::Delivered => break,
+                    Flushed::Aborted => {
+                        warn!(head = ?notification_head(&notification), "Flush aborted, publishing the notification again");
+                    }
+                }
+            }
+
+            if let Some(committed_chain) = notification.committed_chain() {
+                // update exex to new height
+                this.ctx.events.send(ExExEvent::FinishedHeight(committed_chain.tip().num_hash()))?;
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+// publishes or retracts the records of every block of a notification
+fn publish_notification<
+    S: Sink,
+    P: HeaderProvider + StateProviderFactory,
+    C: EthChainSpec + EthereumHardforks,
+    E: ConfigureEvm<Primitives = EthPrimitives>
+>(
+    notification: &ExExNotification,
+    sink: &mut S,
+    provider: &P,
+    chain_spec: &C,
+    datasets: &[Dataset],
+    options: &TransformOptions,
+    tracer: Option<&BlockTracer<E>>
+) -> eyre::Result<()> {
+    match notification {
+        ExExNotification::ChainCommitted { new } => {
+            info!(blocks = ?new.range(), "Received segment ");
+
+            process_committed_chain(
+                new,
+                sink,
+                provider,
+                chain_spec,
+                datasets,
+                options,
+                tracer
+            )?;
+        }
+        ExExNotification::ChainReorged { old, new } => {
+            info!(from_chain = ?old.range(), to_chain = ?new.range(), "Received reorg");
+
+            // retract the orphaned chain first, then publish the new canonical chain
+            process_reverted_chain(
+                old,
+                sink,
+                provider,
+                chain_spec,
+                datasets,
+                options,
+                tracer
+            )?;
+            process_committed_chain(
+                new,
+                sink,
+                provider,
+                chain_spec,
+                datasets,
+                options,
+                tracer
+            )?;
+        }
+        ExExNotification::ChainReverted { old } => {
+            info!(reverted_chain = ?old.range(), "Received revert");
+
+            process_reverted_chain(
+                old,
+                sink,
+                provider,
+                chain_spec,
+                datasets,
+                options,
+                tracer
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+// the last block that is part of the canonical chain after the notification was processed
+fn notification_head(notification: &ExExNotification) -> BlockNumHash {
+    match notification {
+        ExExNotification::ChainCommitted { new } | ExExNotification::ChainReorged { new, .. } => {
+            new.tip().num_hash()
+        }
+        ExExNotification::ChainReverted { old } => old.fork_block(),
+    }
+}
+
+pub fn process_committed_chain<
+    S: Sink,
+    P: HeaderProvider + StateProviderFactory,
+    C: EthChainSpec + EthereumHardforks,
+    E: ConfigureEvm<Primitives = EthPrimitives>
+>(
+    new: &Chain,
+    sink: &mut S,
+    provider: &P,
+    chain_spec: &C,
+    datasets: &[Dataset],
+    options: &TransformOptions,
+    tracer: Option<&BlockTracer<E>>
+) -> eyre::Result<()> {
+    let start_time = std::time::Instant::now();
+
+    let number_of_transactions = u64
+        ::try_from(
+            new
+                .blocks_iter()
+                .map(|block| { block.transaction_count() })
+                .fold(0, |acc, e| acc + e)
+        )
+        .unwrap_or_default();
+
+    let state_changes = StateChanges::new(new);
+    let total_difficulties = total_difficulties(provider, new)?;
+    let traces = tracer.map(|tracer| tracer.trace_chain(provider, new)).transpose()?;
+
+    let mut skipped_logs = SkippedLogs::default();
+
+    // process blocks
+    for (block, receipts) in new.blocks_and_receipts() {
+        let index = (block.number - new.first().number) as usize;
+        let context = BlockContext {
+            state_changes: state_changes.block(block.number),
+            traces: traces.as_ref().map(|traces| &traces[index]),
+            total_difficulty: total_difficulties[index],
+            blob_params: chain_spec.blob_params_at_timestamp(block.timestamp),
+            base_block_reward: base_block_reward(chain_spec, block.number),
+            requests: new.execution_outcome().requests.get(index),
+        };
+        let records = process_block_with_receipts(block, receipts, &context, options, chain_spec.chain_id());
+
+        publish_block_records(&records, sink, datasets)?;
+
+        for transaction_records in records.transactions.iter() {
+            skipped_logs.add(&transaction_records.skipped_logs);
+        }
+    }
+
+    log_segment_processed(new, start_time, number_of_transactions, skipped_logs);
+    Ok(())
+}
+
+pub fn process_reverted_chain<
+    S: Sink,
+    P: HeaderProvider + StateProviderFactory,
+    C: EthChainSpec + EthereumHardforks,
+    E: ConfigureEvm<Primitives = EthPrimitives>
+>(
+    old: &Chain,
+    sink: &mut S,
+    provider: &P,
+    chain_spec: &C,
+    datasets: &[Dataset],
+    options: &TransformOptions,
+    tracer: Option<&BlockTracer<E>>
+) -> eyre::Result<()> {
+    let state_changes = StateChanges::new(old);
+    let total_difficulties = total_difficulties(provider, old)?;
+    let traces = tracer.map(|tracer| tracer.trace_chain(provider, old)).transpose()?;
+
+    // retract blocks from the tip downwards, so consumers undo the newest state first
+    let mut blocks_and_receipts: Vec<_> = old.blocks_and_receipts().collect();
+    blocks_and_receipts.reverse();
+
+    for (block, receipts) in blocks_and_receipts {
+        let index = (block.number - old.first().number) as usize;
+        let context = BlockContext {
+            state_changes: state_changes.block(block.number),
+            traces: traces.as_ref().map(|traces| &traces[index]),
+            total_difficulty: total_difficulties[index],
+            blob_params: chain_spec.blob_params_at_timestamp(block.timestamp),
+            base_block_reward: base_block_reward(chain_spec, block.number),
+            requests: old.execution_outcome().requests.get(index),
+        };
+        let records = retract_block_with_receipts(block, receipts, &context, options, chain_spec.chain_id());
+
+        retract_block_records(&records, sink, datasets)?;
+    }
+
+    info!(blocks = ?old.range(), "Retracted segment");
+    Ok(())
+}
+
+// Total difficulty of each block of the chain, None for blocks after the merge. The total
+// difficulty isn't part of the header, it's the sum of the difficulties up to the block.
+fn total_difficulties<P: HeaderProvider>(provider: &P, chain: &Chain) -> eyre::Result<Vec<Option<U256>>> {
+    let first = chain.first();
+    let mut total_difficulty = match first.number {
+        0 => Some(U256::ZERO),
+        _ if first.difficulty.is_zero() => None,
+        number => provider.header_td_by_number(number - 1)?,
+    };
+
+    Ok(
+        chain
+            .blocks_iter()
+            .map(|block| {
+                total_difficulty = total_difficulty.map(|total_difficulty| total_difficulty + block.difficulty);
+                total_difficulty.filter(|_| !block.difficulty.is_zero())
+            })
+            .collect()
+    )
+}
+
+fn publish_block_records<S: Sink>(
+    records: &EmitterBlockRecords,
+    sink: &mut S,
+    datasets: &[Dataset]
+) -> eyre::Result<()> {
+    if datasets.contains(&Dataset::Blocks) {
+        sink.publish_record(&records.block)?;
+    }
+
+    for transaction_records in records.transactions.iter() {
+        if datasets.contains(&Dataset::Transactions) {
+            sink.publish_record(&transaction_records.transaction)?;
+        }
+
+        if datasets.contains(&Dataset::Receipts) {
+            sink.publish_record(&transaction_records.receipt)?;
+        }
+
+        if datasets.contains(&Dataset::Logs) {
+            for emitter_log in transaction_records.logs.iter() {
+                sink.publish_record(emitter_log)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Erc20Transfers) {
+            for emitter_erc20_transfer in transaction_records.erc20_transfers.iter() {
+                sink.publish_record(emitter_erc20_transfer)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Erc20Approvals) {
+            for emitter_erc20_approval in transaction_records.erc20_approvals.iter() {
+                sink.publish_record(emitter_erc20_approval)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::NftTransfers) {
+            for emitter_nft_transfer in transaction_records.nft_transfers.iter() {
+                sink.publish_record(emitter_nft_transfer)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DexSwaps) {
+            for emitter_dex_event in transaction_records.dex_events.iter() {
+                sink.publish_record(emitter_dex_event)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DecodedLogs) {
+            for emitter_decoded_log in transaction_records.decoded_logs.iter() {
+                sink.publish_record(emitter_decoded_log)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DecodedCalls) {
+            if let Some(emitter_decoded_call) = &transaction_records.decoded_call {
+                sink.publish_record(emitter_decoded_call)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Traces) {
+            for emitter_trace in transaction_records.traces.iter() {
+                sink.publish_record(emitter_trace)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Authorizations) {
+            for emitter_authorization in transaction_records.authorizations.iter() {
+                sink.publish_record(emitter_authorization)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::AccessLists) {
+            for emitter_access_list_item in transaction_records.access_list.iter() {
+                sink.publish_record(emitter_access_list_item)?;
+            }
+        }
+    }
+
+    if datasets.contains(&Dataset::Withdrawals) {
+        for emitter_withdrawal in records.withdrawals.iter() {
+            sink.publish_record(emitter_withdrawal)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::EthTransfers) {
+        for emitter_eth_transfer in records.eth_transfers.iter() {
+            sink.publish_record(emitter_eth_transfer)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::BalanceDiffs) {
+        for emitter_balance_diff in records.balance_diffs.iter() {
+            sink.publish_record(emitter_balance_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::NonceDiffs) {
+        for emitter_nonce_diff in records.nonce_diffs.iter() {
+            sink.publish_record(emitter_nonce_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::StorageDiffs) {
+        for emitter_storage_diff in records.storage_diffs.iter() {
+            sink.publish_record(emitter_storage_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::Contracts) {
+        for emitter_contract in records.contracts.iter() {
+            sink.publish_record(emitter_contract)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::ElRequests) {
+        for emitter_el_request in records.el_requests.iter() {
+            sink.publish_record(emitter_el_request)?;
+        }
+    }
+
+    Ok(())
+}
+
+// retract in the reverse order of publish_block_records
+fn retract_block_records<S: Sink>(
+    records: &EmitterBlockRecords,
+    sink: &mut S,
+    datasets: &[Dataset]
+) -> eyre::Result<()> {
+    if datasets.contains(&Dataset::ElRequests) {
+        for emitter_el_request in records.el_requests.iter().rev() {
+            sink.retract(emitter_el_request)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::Contracts) {
+        for emitter_contract in records.contracts.iter().rev() {
+            sink.retract(emitter_contract)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::StorageDiffs) {
+        for emitter_storage_diff in records.storage_diffs.iter().rev() {
+            sink.retract(emitter_storage_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::NonceDiffs) {
+        for emitter_nonce_diff in records.nonce_diffs.iter().rev() {
+            sink.retract(emitter_nonce_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::BalanceDiffs) {
+        for emitter_balance_diff in records.balance_diffs.iter().rev() {
+            sink.retract(emitter_balance_diff)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::EthTransfers) {
+        for emitter_eth_transfer in records.eth_transfers.iter().rev() {
+            sink.retract(emitter_eth_transfer)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::Withdrawals) {
+        for emitter_withdrawal in records.withdrawals.iter().rev() {
+            sink.retract(emitter_withdrawal)?;
+        }
+    }
+
+    for transaction_records in records.transactions.iter().rev() {
+        if datasets.contains(&Dataset::AccessLists) {
+            for emitter_access_list_item in transaction_records.access_list.iter().rev() {
+                sink.retract(emitter_access_list_item)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Authorizations) {
+            for emitter_authorization in transaction_records.authorizations.iter().rev() {
+                sink.retract(emitter_authorization)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Traces) {
+            for emitter_trace in transaction_records.traces.iter().rev() {
+                sink.retract(emitter_trace)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DecodedCalls) {
+            if let Some(emitter_decoded_call) = &transaction_records.decoded_call {
+                sink.retract(emitter_decoded_call)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DecodedLogs) {
+            for emitter_decoded_log in transaction_records.decoded_logs.iter().rev() {
+                sink.retract(emitter_decoded_log)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::DexSwaps) {
+            for emitter_dex_event in transaction_records.dex_events.iter().rev() {
+                sink.retract(emitter_dex_event)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::NftTransfers) {
+            for emitter_nft_transfer in transaction_records.nft_transfers.iter().rev() {
+                sink.retract(emitter_nft_transfer)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Erc20Approvals) {
+            for emitter_erc20_approval in transaction_records.erc20_approvals.iter().rev() {
+                sink.retract(emitter_erc20_approval)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Erc20Transfers) {
+            for emitter_erc20_transfer in transaction_records.erc20_transfers.iter().rev() {
+                sink.retract(emitter_erc20_transfer)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Logs) {
+            for emitter_log in transaction_records.logs.iter().rev() {
+                sink.retract(emitter_log)?;
+            }
+        }
+
+        if datasets.contains(&Dataset::Receipts) {
+            sink.retract(&transaction_records.receipt)?;
+        }
+
+        if datasets.contains(&Dataset::Transactions) {
+            sink.retract(&transaction_records.transaction)?;
+        }
+    }
+
+    if datasets.contains(&Dataset::Blocks) {
+        sink.retract(&records.block)?;
+    }
+
+    Ok(())
+}
+
+fn log_segment_processed(
+    new: &Chain,
+    start_time: std::time::Instant,
+    number_of_transactions: u64,
+    skipped_logs: SkippedLogs
+) {
+    let processed_blocks = new.tip().number - new.first().number + 1;
+    let blocks_per_second = (processed_blocks as f64) / start_time.elapsed().as_secs_f64();
+    let tx_per_second = (number_of_transactions as f64) / start_time.elapsed().as_secs_f64();
+    info!(
+        blocks = ?new.range(),
+        processed_blocks,
+        blocks_per_second,
+        transactions=number_of_transactions,
+        tx_per_second,
+        skipped_erc20_transfers=skipped_logs.erc20_transfers,
+        skipped_erc20_approvals=skipped_logs.erc20_approvals,
+        skipped_nft_transfers=skipped_logs.nft_transfers,
+        skipped_dex_events=skipped_logs.dex_events,
+        skipped_decoded_logs=skipped_logs.decoded_logs,
+        "Processed segment"
+    )
+}
@@ -3,6 +3,16 @@
 // available at http://aws.amazon.com/agreement or other written agreement between
 // Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
 
+pub mod abi;        // Decoding of logs and calls with contract ABIs
+pub mod args;       // CLI arguments shared by the emitters
+pub mod dex;        // Uniswap V2 and V3 pool events
+pub mod emitter;    // ExEx core, independent of the output
+pub mod quantity;   // Lossless encoding of large numbers
+pub mod requests;   // EIP-7685 execution-layer requests
+pub mod sink;       // Output of the emitter
+pub mod state;      // State changes of the blocks of a chain
+pub mod tokens;     // Standard token events
+pub mod trace;      // Re-execution of blocks with a tracing inspector
 pub mod transform;  // Expose transform module
 
 #[cfg(test)]
@@ -0,0 +1,58 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use alloy::primitives::U256;
+use serde::{ Serialize, Serializer };
+
+use crate::args::NumericEncoding;
+
+// U256 and u128 values, serialized with the configured encoding. JSON numbers are parsed as
+// doubles by many consumers, which silently loses precision above 2^53.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quantity {
+    pub value: U256,
+    pub encoding: NumericEncoding,
+}
+
+impl Quantity {
+    pub fn new(value: U256, encoding: NumericEncoding) -> Self {
+        Self { value, encoding }
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.encoding {
+            NumericEncoding::Decimal => serializer.serialize_str(&self.value.to_string()),
+            NumericEncoding::Hex => serializer.serialize_str(&format!("{:#x}", self.value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(value: U256, encoding: NumericEncoding) -> String {
+        serde_json::to_string(&Quantity::new(value, encoding)).unwrap()
+    }
+
+    #[test]
+    fn decimal() {
+        assert_eq!(serialize(U256::from(1_000_000_000u64), NumericEncoding::Decimal), r#""1000000000""#);
+        assert_eq!(serialize(U256::ZERO, NumericEncoding::Decimal), r#""0""#);
+        assert_eq!(
+            serialize(U256::MAX, NumericEncoding::Decimal),
+            format!(r#""{}""#, "115792089237316195423570985008687907853269984665640564039457584007913129639935")
+        );
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(serialize(U256::from(1_000_000_000u64), NumericEncoding::Hex), r#""0x3b9aca00""#);
+        assert_eq!(serialize(U256::ZERO, NumericEncoding::Hex), r#""0x0""#);
+        assert_eq!(serialize(U256::MAX, NumericEncoding::Hex), format!(r#""0x{}""#, "f".repeat(64)));
+    }
+}
@@ -0,0 +1,202 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use alloy::{
+    eips::{
+        eip6110::DEPOSIT_REQUEST_TYPE,
+        eip7002::WITHDRAWAL_REQUEST_TYPE,
+        eip7251::CONSOLIDATION_REQUEST_TYPE,
+        eip7685::Requests,
+    },
+    primitives::{ Address, Bytes, FixedBytes },
+};
+
+// sizes of the encoded requests, the data of a request type is the concatenation of its requests
+const DEPOSIT_SIZE: usize = 48 + 32 + 8 + 96 + 8;
+const WITHDRAWAL_SIZE: usize = 20 + 48 + 8;
+const CONSOLIDATION_SIZE: usize = 20 + 48 + 48;
+
+// EIP-7685 execution-layer request, amounts in gwei
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElRequest {
+    // EIP-6110, from the logs of the deposit contract
+    Deposit {
+        pubkey: Bytes,
+        withdrawal_credentials: FixedBytes<32>,
+        amount: u64,
+        signature: Bytes,
+        index: u64,
+    },
+    // EIP-7002, an amount of 0 is a full exit
+    Withdrawal {
+        source_address: Address,
+        validator_pubkey: Bytes,
+        amount: u64,
+    },
+    // EIP-7251
+    Consolidation {
+        source_address: Address,
+        source_pubkey: Bytes,
+        target_pubkey: Bytes,
+    },
+}
+
+// Decodes the requests of a block, grouped by type in ascending type order. Malformed requests
+// and unknown request types are left out.
+pub fn decode_requests(requests: &Requests) -> Vec<ElRequest> {
+    requests
+        .iter()
+        .filter_map(|request| decode_request(request))
+        .flatten()
+        .collect()
+}
+
+// Decodes the requests of one type: the type byte followed by the concatenation of its requests.
+// None for unknown types and for data that isn't a non-empty multiple of the request size, e.g.
+// truncated requests.
+pub fn decode_request(request: &[u8]) -> Option<Vec<ElRequest>> {
+    let (request_type, data) = request.split_first()?;
+
+    let size = match *request_type {
+        DEPOSIT_REQUEST_TYPE => DEPOSIT_SIZE,
+        WITHDRAWAL_REQUEST_TYPE => WITHDRAWAL_SIZE,
+        CONSOLIDATION_REQUEST_TYPE => CONSOLIDATION_SIZE,
+        _ => return None,
+    };
+
+    if data.is_empty() || data.len() % size != 0 {
+        return None;
+    }
+
+    let decoded = data
+        .chunks_exact(size)
+        .map(|chunk| match *request_type {
+            // amount and index are little-endian, like in the deposit contract
+            DEPOSIT_REQUEST_TYPE => ElRequest::Deposit {
+                pubkey: Bytes::copy_from_slice(&chunk[0..48]),
+                withdrawal_credentials: FixedBytes::from_slice(&chunk[48..80]),
+                amount: u64::from_le_bytes(chunk[80..88].try_into().unwrap()),
+                signature: Bytes::copy_from_slice(&chunk[88..184]),
+                index: u64::from_le_bytes(chunk[184..192].try_into().unwrap()),
+            },
+            // amount is little-endian, like the SSZ WithdrawalRequest the system contract returns
+            WITHDRAWAL_REQUEST_TYPE => ElRequest::Withdrawal {
+                source_address: Address::from_slice(&chunk[0..20]),
+                validator_pubkey: Bytes::copy_from_slice(&chunk[20..68]),
+                amount: u64::from_le_bytes(chunk[68..76].try_into().unwrap()),
+            },
+            // consolidations, unknown types were rejected above
+            _ => ElRequest::Consolidation {
+                source_address: Address::from_slice(&chunk[0..20]),
+                source_pubkey: Bytes::copy_from_slice(&chunk[20..68]),
+                target_pubkey: Bytes::copy_from_slice(&chunk[68..116]),
+            },
+        })
+        .collect();
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // request of the given type with the fields concatenated
+    fn request(request_type: u8, fields: &[&[u8]]) -> Vec<u8> {
+        let mut request = vec![request_type];
+        fields.iter().for_each(|field| request.extend_from_slice(field));
+        request
+    }
+
+    fn deposit() -> Vec<u8> {
+        request(
+            DEPOSIT_REQUEST_TYPE,
+            &[&[0x11; 48], &[0x22; 32], &32_000_000_000u64.to_le_bytes(), &[0x33; 96], &7u64.to_le_bytes()]
+        )
+    }
+
+    #[test]
+    fn deposit_fields() {
+        assert_eq!(deposit().len(), 1 + DEPOSIT_SIZE);
+        assert_eq!(
+            decode_request(&deposit()),
+            Some(vec![ElRequest::Deposit {
+                pubkey: Bytes::from(vec![0x11; 48]),
+                withdrawal_credentials: FixedBytes::repeat_byte(0x22),
+                amount: 32_000_000_000,
+                signature: Bytes::from(vec![0x33; 96]),
+                index: 7,
+            }])
+        );
+    }
+
+    #[test]
+    fn withdrawal_fields() {
+        let withdrawal = request(
+            WITHDRAWAL_REQUEST_TYPE,
+            &[&[0x44; 20], &[0x55; 48], &0x0102_0304_0506_0708u64.to_le_bytes()]
+        );
+
+        assert_eq!(
+            decode_request(&withdrawal),
+            Some(vec![ElRequest::Withdrawal {
+                source_address: Address::repeat_byte(0x44),
+                validator_pubkey: Bytes::from(vec![0x55; 48]),
+                amount: 0x0102_0304_0506_0708,
+            }])
+        );
+    }
+
+    #[test]
+    fn consolidation_fields() {
+        let consolidation = request(CONSOLIDATION_REQUEST_TYPE, &[&[0x66; 20], &[0x77; 48], &[0x88; 48]]);
+
+        assert_eq!(
+            decode_request(&consolidation),
+            Some(vec![ElRequest::Consolidation {
+                source_address: Address::repeat_byte(0x66),
+                source_pubkey: Bytes::from(vec![0x77; 48]),
+                target_pubkey: Bytes::from(vec![0x88; 48]),
+            }])
+        );
+    }
+
+    #[test]
+    fn multiple_requests_of_a_type() {
+        let mut deposits = deposit();
+        deposits.extend_from_slice(&deposit()[1..]);
+
+        assert_eq!(decode_request(&deposits).map(|requests| requests.len()), Some(2));
+    }
+
+    #[test]
+    fn malformed_requests() {
+        // truncated
+        let deposit = deposit();
+        assert_eq!(decode_request(&deposit[..deposit.len() - 1]), None);
+
+        // misaligned, a full request followed by part of another
+        let mut misaligned = deposit.clone();
+        misaligned.extend_from_slice(&[0; 10]);
+        assert_eq!(decode_request(&misaligned), None);
+
+        // type without data, unknown type
+        assert_eq!(decode_request(&[WITHDRAWAL_REQUEST_TYPE]), None);
+        assert_eq!(decode_request(&[0x03; 77]), None);
+        assert_eq!(decode_request(&[]), None);
+    }
+
+    #[test]
+    fn requests_of_a_block() {
+        let withdrawal = request(WITHDRAWAL_REQUEST_TYPE, &[&[0x44; 20], &[0x55; 48], &1_234_567_890u64.to_le_bytes()]);
+        let truncated = request(CONSOLIDATION_REQUEST_TYPE, &[&[0x66; 20]]);
+        let requests = Requests::new(vec![deposit().into(), withdrawal.into(), truncated.into()]);
+
+        let decoded = decode_requests(&requests);
+        assert_eq!(decoded.len(), 2);
+        assert!(matches!(decoded[0], ElRequest::Deposit { .. }));
+        assert!(matches!(decoded[1], ElRequest::Withdrawal { amount: 1_234_567_890, .. }));
+    }
+}
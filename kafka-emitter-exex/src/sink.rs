@@ -0,0 +1,35 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use reth::rpc::types::BlockNumHash;
+
+use crate::transform::Record;
+
+// outcome of flushing the records of a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flushed {
+    // every record was delivered
+    Delivered,
+    // the records were discarded (e.g. an aborted Kafka transaction), publish the notification again
+    Aborted,
+}
+
+// Output of the emitter. The emitter calls `publish_record` or `retract` for every record of a
+// notification, then `flush` once, before it reports the height to reth.
+pub trait Sink {
+    // publish a record to the topic of its dataset
+    fn publish_record<R: Record>(&mut self, record: &R) -> eyre::Result<()>;
+
+    // publish a retraction record (`removed` is set) of a reorged or reverted record
+    fn retract<R: Record>(&mut self, record: &R) -> eyre::Result<()>;
+
+    // deliver all records published so far, `head` is the last canonical block afterwards
+    fn flush(&mut self, head: BlockNumHash) -> eyre::Result<Flushed>;
+
+    // last block the sink fully delivered in a previous run, used to resume after it
+    fn last_cursor(&mut self) -> eyre::Result<Option<BlockNumHash>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,234 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use std::collections::HashMap;
+
+use alloy::primitives::{ Address, U256 };
+use reth_execution_types::Chain;
+use revm::{ database::BundleState, state::AccountInfo };
+
+// account before and after a block, None if the account didn't exist
+#[derive(Debug, Clone)]
+pub struct AccountChange {
+    pub address: Address,
+    pub previous: Option<AccountInfo>,
+    pub new: Option<AccountInfo>,
+}
+
+// storage slot before and after a block, 0 for slots that weren't set
+#[derive(Debug, Clone)]
+pub struct StorageChange {
+    pub address: Address,
+    pub slot: U256,
+    pub previous: U256,
+    pub new: U256,
+}
+
+// state changes of a single block
+#[derive(Debug, Default)]
+pub struct BlockStateChanges {
+    pub accounts: Vec<AccountChange>,
+    pub storage: Vec<StorageChange>,
+}
+
+// state changes of every block of a chain, derived from the bundle state of its execution outcome
+#[derive(Debug)]
+pub struct StateChanges {
+    first_block: u64,
+    blocks: Vec<BlockStateChanges>,
+}
+
+impl StateChanges {
+    pub fn new(chain: &Chain) -> Self {
+        Self::from_bundle(&chain.execution_outcome().bundle, chain.first().number, chain.len())
+    }
+
+    // changes of `len` blocks starting at `first_block`, the bundle has one revert per block
+    fn from_bundle(bundle: &BundleState, first_block: u64, len: usize) -> Self {
+        // the bundle holds the state after the tip and, for every block, the values before the
+        // block (reverts). Walking the reverts from the tip backwards yields the values after
+        // each block as well.
+        let reverts = bundle.reverts.to_plain_state_reverts();
+        let mut blocks: Vec<BlockStateChanges> = (0..len).map(|_| BlockStateChanges::default()).collect();
+
+        // state after the block that is currently processed
+        let mut current: HashMap<Address, Option<AccountInfo>> = HashMap::new();
+
+        for (index, account_reverts) in reverts.accounts.into_iter().enumerate().rev() {
+            let Some(block) = blocks.get_mut(index) else {
+                continue;
+            };
+
+            for (address, previous) in account_reverts {
+                let new = current
+                    .remove(&address)
+                    .unwrap_or_else(|| {
+                        bundle.state.get(&address).and_then(|account| account.info.clone())
+                    })
+                    .map(|info| with_code(info, bundle));
+
+                current.insert(address, previous.clone());
+
+                block.accounts.push(AccountChange { address, previous, new });
+            }
+
+            block.accounts.sort_by_key(|change| change.address);
+        }
+
+        // same for the storage, slots of wiped storage (SELFDESTRUCT) are only known if they
+        // were touched before
+        let mut current_storage: HashMap<(Address, U256), U256> = HashMap::new();
+
+        for (index, storage_reverts) in reverts.storage.into_iter().enumerate().rev() {
+            let Some(block) = blocks.get_mut(index) else {
+                continue;
+            };
+
+            for storage_revert in storage_reverts {
+                let address = storage_revert.address;
+
+                for (slot, revert_to_slot) in storage_revert.storage_revert {
+                    let previous = revert_to_slot.to_previous_value();
+                    let new = current_storage
+                        .remove(&(address, slot))
+                        .unwrap_or_else(|| {
+                            bundle
+                                .account(&address)
+                                .and_then(|account| account.storage_slot(slot))
+                                .unwrap_or_default()
+                        });
+
+                    current_storage.insert((address, slot), previous);
+
+                    if previous != new {
+                        block.storage.push(StorageChange { address, slot, previous, new });
+                    }
+                }
+            }
+
+            block.storage.sort_by_key(|change| (change.address, change.slot));
+        }
+
+        Self { first_block, blocks }
+    }
+
+    pub fn block(&self, block_number: u64) -> &BlockStateChanges {
+        &self.blocks[(block_number - self.first_block) as usize]
+    }
+}
+
+// the bundle stores new bytecode separately from the accounts
+fn with_code(mut info: AccountInfo, bundle: &BundleState) -> AccountInfo {
+    if info.code.is_none() {
+        info.code = bundle.contracts.get(&info.code_hash).cloned();
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use revm::database::BundleBuilder;
+
+    fn account(balance: u64) -> AccountInfo {
+        AccountInfo { balance: U256::from(balance), ..Default::default() }
+    }
+
+    fn balance(info: &Option<AccountInfo>) -> Option<U256> {
+        info.as_ref().map(|info| info.balance)
+    }
+
+    #[test]
+    fn accounts_before_and_after_each_block() {
+        let a = Address::with_last_byte(1);
+        let b = Address::with_last_byte(2);
+
+        // a: 1 -> 2 in block 10, 2 -> 3 in block 11. b: created with 5 in block 11
+        let bundle = BundleBuilder::new(10..=11)
+            .state_original_account_info(a, account(1))
+            .state_present_account_info(a, account(3))
+            .state_present_account_info(b, account(5))
+            .revert_address(10, a)
+            .revert_account_info(10, a, Some(Some(account(1))))
+            .revert_address(11, a)
+            .revert_account_info(11, a, Some(Some(account(2))))
+            .revert_address(11, b)
+            .revert_account_info(11, b, Some(None))
+            .build();
+
+        let state_changes = StateChanges::from_bundle(&bundle, 10, 2);
+
+        let block = state_changes.block(10);
+        assert_eq!(block.accounts.len(), 1);
+        assert_eq!(block.accounts[0].address, a);
+        assert_eq!(balance(&block.accounts[0].previous), Some(U256::from(1)));
+        assert_eq!(balance(&block.accounts[0].new), Some(U256::from(2)));
+
+        let block = state_changes.block(11);
+        assert_eq!(block.accounts.len(), 2);
+        assert_eq!(block.accounts[0].address, a);
+        assert_eq!(balance(&block.accounts[0].previous), Some(U256::from(2)));
+        assert_eq!(balance(&block.accounts[0].new), Some(U256::from(3)));
+        assert_eq!(block.accounts[1].address, b);
+        assert_eq!(balance(&block.accounts[1].previous), None);
+        assert_eq!(balance(&block.accounts[1].new), Some(U256::from(5)));
+    }
+
+    #[test]
+    fn blocks_without_changes() {
+        let a = Address::with_last_byte(1);
+
+        // a only changed in block 10, block 11 has no reverts
+        let bundle = BundleBuilder::new(10..=11)
+            .state_original_account_info(a, account(1))
+            .state_present_account_info(a, account(2))
+            .revert_address(10, a)
+            .revert_account_info(10, a, Some(Some(account(1))))
+            .build();
+
+        let state_changes = StateChanges::from_bundle(&bundle, 10, 2);
+
+        assert_eq!(balance(&state_changes.block(10).accounts[0].new), Some(U256::from(2)));
+        assert!(state_changes.block(11).accounts.is_empty());
+    }
+
+    #[test]
+    fn storage_before_and_after_each_block() {
+        let a = Address::with_last_byte(1);
+        let (slot1, slot2, slot3) = (U256::from(1), U256::from(2), U256::from(3));
+
+        // slot 1: 0 -> 5 in block 10, 5 -> 6 in block 11. slot 2: 0 -> 7 in block 11.
+        // slot 3: written back to its previous value in block 11
+        let bundle = BundleBuilder::new(10..=11)
+            .state_present_account_info(a, account(0))
+            .state_storage(
+                a,
+                [
+                    (slot1, (U256::ZERO, U256::from(6))),
+                    (slot2, (U256::ZERO, U256::from(7))),
+                    (slot3, (U256::from(9), U256::from(9))),
+                ].into_iter().collect()
+            )
+            .revert_address(10, a)
+            .revert_storage(10, a, vec![(slot1, U256::ZERO)])
+            .revert_address(11, a)
+            .revert_storage(11, a, vec![(slot1, U256::from(5)), (slot2, U256::ZERO), (slot3, U256::from(9))])
+            .build();
+
+        let state_changes = StateChanges::from_bundle(&bundle, 10, 2);
+
+        let changes = |block_number| -> Vec<(U256, U256, U256)> {
+            state_changes
+                .block(block_number)
+                .storage.iter()
+                .map(|change| (change.slot, change.previous, change.new))
+                .collect()
+        };
+
+        assert_eq!(changes(10), vec![(slot1, U256::ZERO, U256::from(5))]);
+        assert_eq!(changes(11), vec![(slot1, U256::from(5), U256::from(6)), (slot2, U256::ZERO, U256::from(7))]);
+    }
+}
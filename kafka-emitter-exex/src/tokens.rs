@@ -0,0 +1,187 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use alloy::{ primitives::Log, sol_types::SolEvent };
+
+pub mod erc20 {
+    alloy::sol! {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+        event Approval(address indexed owner, address indexed spender, uint256 value);
+    }
+}
+
+// same signature as the ERC-20 Transfer, but with the token id as third indexed topic
+pub mod erc721 {
+    alloy::sol! {
+        event Transfer(address indexed from, address indexed to, uint256 indexed tokenId);
+    }
+}
+
+pub mod erc1155 {
+    alloy::sol! {
+        event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value);
+        event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values);
+    }
+}
+
+// ERC-20 events have two indexed addresses and the amount as data
+pub fn decode_erc20_event<E: SolEvent>(log: &Log) -> Option<E> {
+    if log.data.data.len() != 32 {
+        return None;
+    }
+    decode_event(log, 3, 2)
+}
+
+// ERC-721 transfers have everything indexed and no data
+pub fn decode_erc721_transfer(log: &Log) -> Option<erc721::Transfer> {
+    if !log.data.data.is_empty() {
+        return None;
+    }
+    decode_event(log, 4, 2)
+}
+
+// ERC-1155 transfers have three indexed addresses, batches need as many ids as values
+pub fn decode_erc1155_transfer_single(log: &Log) -> Option<erc1155::TransferSingle> {
+    decode_event(log, 4, 3)
+}
+
+pub fn decode_erc1155_transfer_batch(log: &Log) -> Option<erc1155::TransferBatch> {
+    decode_event::<erc1155::TransferBatch>(log, 4, 3).filter(|batch| batch.ids.len() == batch.values.len())
+}
+
+// Decodes a log with the signature of a standard token event. None if the log doesn't follow the
+// standard, e.g. wrong number of topics or dirty address topics.
+pub(crate) fn decode_event<E: SolEvent>(log: &Log, topics: usize, address_topics: usize) -> Option<E> {
+    if log.topics().len() != topics {
+        return None;
+    }
+
+    // addresses are left-padded with zeros
+    if log.topics()[1..=address_topics].iter().any(|topic| topic[..12].iter().any(|byte| *byte != 0)) {
+        return None;
+    }
+
+    E::decode_raw_log(log.topics().iter().copied(), &log.data.data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloy::primitives::{ Address, LogData, U256 };
+
+    fn log(data: LogData) -> Log {
+        Log { address: Address::with_last_byte(0xaa), data }
+    }
+
+    fn erc20_transfer() -> LogData {
+        erc20::Transfer {
+            from: Address::with_last_byte(1),
+            to: Address::with_last_byte(2),
+            value: U256::from(100),
+        }.encode_log_data()
+    }
+
+    #[test]
+    fn decodes_erc20_transfer() {
+        let transfer = decode_erc20_event::<erc20::Transfer>(&log(erc20_transfer())).unwrap();
+
+        assert_eq!(transfer.from, Address::with_last_byte(1));
+        assert_eq!(transfer.to, Address::with_last_byte(2));
+        assert_eq!(transfer.value, U256::from(100));
+    }
+
+    #[test]
+    fn wrong_number_of_topics() {
+        let data = erc20_transfer();
+
+        let mut topics = data.topics().to_vec();
+        topics.pop();
+        assert!(decode_erc20_event::<erc20::Transfer>(&log(LogData::new_unchecked(topics, data.data.clone()))).is_none());
+
+        let mut topics = data.topics().to_vec();
+        topics.push(topics[1]);
+        assert!(decode_event::<erc20::Transfer>(&log(LogData::new_unchecked(topics, data.data.clone())), 3, 2).is_none());
+    }
+
+    #[test]
+    fn dirty_address_topic() {
+        let data = erc20_transfer();
+
+        for index in 1..=2 {
+            let mut topics = data.topics().to_vec();
+            topics[index][0] = 1;
+            let log = log(LogData::new_unchecked(topics, data.data.clone()));
+            assert!(decode_erc20_event::<erc20::Transfer>(&log).is_none());
+        }
+    }
+
+    #[test]
+    fn erc20_data_not_a_single_word() {
+        let data = erc20_transfer();
+
+        let mut longer = data.data.to_vec();
+        longer.extend([0; 32]);
+        let log = log(LogData::new_unchecked(data.topics().to_vec(), longer.into()));
+        assert!(decode_erc20_event::<erc20::Transfer>(&log).is_none());
+    }
+
+    #[test]
+    fn erc721_transfer() {
+        let data = erc721::Transfer {
+            from: Address::ZERO,
+            to: Address::with_last_byte(2),
+            tokenId: U256::MAX,
+        }.encode_log_data();
+
+        let transfer = decode_erc721_transfer(&log(data.clone())).unwrap();
+        assert_eq!(transfer.to, Address::with_last_byte(2));
+        assert_eq!(transfer.tokenId, U256::MAX);
+
+        // ERC-20 transfers share the signature, but have 3 topics and the amount as data
+        assert!(decode_erc721_transfer(&log(erc20_transfer())).is_none());
+
+        let log = log(LogData::new_unchecked(data.topics().to_vec(), vec![0; 32].into()));
+        assert!(decode_erc721_transfer(&log).is_none());
+    }
+
+    #[test]
+    fn erc1155_transfers() {
+        let single = erc1155::TransferSingle {
+            operator: Address::with_last_byte(3),
+            from: Address::with_last_byte(1),
+            to: Address::with_last_byte(2),
+            id: U256::from(7),
+            value: U256::from(10),
+        }.encode_log_data();
+
+        let transfer = decode_erc1155_transfer_single(&log(single.clone())).unwrap();
+        assert_eq!(transfer.operator, Address::with_last_byte(3));
+        assert_eq!(transfer.id, U256::from(7));
+        assert_eq!(transfer.value, U256::from(10));
+
+        // the operator is an address topic as well
+        let mut topics = single.topics().to_vec();
+        topics[1][0] = 1;
+        assert!(decode_erc1155_transfer_single(&log(LogData::new_unchecked(topics, single.data.clone()))).is_none());
+
+        let batch = |ids: Vec<U256>, values: Vec<U256>| {
+            log(
+                erc1155::TransferBatch {
+                    operator: Address::with_last_byte(3),
+                    from: Address::with_last_byte(1),
+                    to: Address::with_last_byte(2),
+                    ids,
+                    values,
+                }.encode_log_data()
+            )
+        };
+
+        let transfer = decode_erc1155_transfer_batch(&batch(vec![U256::from(1), U256::from(2)], vec![U256::from(5), U256::from(6)]));
+        assert_eq!(transfer.unwrap().values, vec![U256::from(5), U256::from(6)]);
+
+        assert!(decode_erc1155_transfer_batch(&batch(vec![U256::from(1), U256::from(2)], vec![U256::from(5)])).is_none());
+    }
+}
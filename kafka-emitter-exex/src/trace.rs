@@ -0,0 +1,69 @@
+// © 2025 Amazon Web Services, Inc. or its affiliates. All Rights Reserved.
+// This AWS Content is provided subject to the terms of the AWS Customer Agreement
+// available at http://aws.amazon.com/agreement or other written agreement between
+// Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
+
+use alloy::rpc::types::trace::parity::TransactionTrace;
+use reth::{ primitives::{ EthPrimitives, RecoveredBlock, TransactionSigned }, providers::StateProviderFactory };
+use reth_evm::{ execute::BlockExecutor, ConfigureEvm, Evm };
+use reth_execution_types::Chain;
+use reth_revm::{ database::StateProviderDatabase, db::State };
+use revm::Database;
+use revm_inspectors::tracing::{ TracingInspector, TracingInspectorConfig };
+
+// flat Parity-style traces of every transaction of every block of a chain
+pub type ChainTraces = Vec<Vec<Vec<TransactionTrace>>>;
+
+// Re-executes blocks with a tracing inspector to get the internal calls of their transactions,
+// which are not part of the blocks and receipts.
+#[derive(Debug, Clone)]
+pub struct BlockTracer<E> {
+    evm_config: E,
+}
+
+impl<E: ConfigureEvm<Primitives = EthPrimitives>> BlockTracer<E> {
+    pub fn new(evm_config: E) -> Self {
+        Self { evm_config }
+    }
+
+    // The blocks are executed one after the other on top of the state before the first block. Only
+    // the parent of the chain has to be known to the node, so orphaned chains can be traced as well.
+    pub fn trace_chain<P: StateProviderFactory>(&self, provider: &P, chain: &Chain) -> eyre::Result<ChainTraces> {
+        let state_provider = provider.history_by_block_hash(chain.first().parent_hash)?;
+        let mut db = State::builder().with_database(StateProviderDatabase::new(state_provider)).build();
+
+        chain
+            .blocks_iter()
+            .map(|block| self.trace_block(&mut db, block))
+            .collect()
+    }
+
+    fn trace_block<DB: Database>(
+        &self,
+        db: &mut State<DB>,
+        block: &RecoveredBlock<alloy::consensus::Block<TransactionSigned>>
+    ) -> eyre::Result<Vec<Vec<TransactionTrace>>>
+        where DB::Error: Send + Sync + 'static
+    {
+        let inspector = TracingInspector::new(TracingInspectorConfig::default_parity());
+        let evm = self.evm_config.evm_with_env_and_inspector(db, self.evm_config.evm_env(block.header()), inspector);
+        let mut executor = self.evm_config.create_executor(evm, self.evm_config.context_for_block(block.sealed_block()));
+
+        // system calls (EIP-4788, EIP-2935) run before the transactions, but are not traced
+        executor.apply_pre_execution_changes()?;
+
+        let mut traces = Vec::with_capacity(block.body().transactions.len());
+        for transaction in block.transactions_recovered() {
+            executor.evm_mut().inspector_mut().fuse();
+            executor.execute_transaction(transaction)?;
+
+            let inspector = executor.evm_mut().inspector_mut().clone();
+            traces.push(inspector.into_parity_builder().into_transaction_traces());
+        }
+
+        // rewards and withdrawals, so the next block starts from the right state
+        executor.apply_post_execution_changes()?;
+
+        Ok(traces)
+    }
+}
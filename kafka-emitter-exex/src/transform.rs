@@ -3,12 +3,78 @@
 // available at http://aws.amazon.com/agreement or other written agreement between
 // Customer and either Amazon Web Services, Inc. or Amazon Web Services EMEA SARL or both.
 
+use std::sync::Arc;
+
 use alloy::{
-    consensus::{ BlockHeader, Transaction, TxReceipt },
-    primitives::{ Address, Bloom, Bytes, FixedBytes },
+    consensus::{ transaction::to_eip155_value, BlockHeader, Transaction, TxReceipt, TxType },
+    eips::{ eip2718::Encodable2718, eip4844::DATA_GAS_PER_BLOB, eip7685::Requests, eip7840::BlobParams },
+    primitives::{ Address, Bloom, Bytes, FixedBytes, U256 },
+    rpc::types::trace::parity::{ Action, CallType, TraceOutput, TransactionTrace },
+    sol_types::SolEvent,
 };
 
 use reth::primitives::{ TransactionSigned, Receipt };
+use reth_consensus_common::calc::{ block_reward, ommer_reward };
+use revm::primitives::KECCAK_EMPTY;
+
+use crate::{
+    abi::{ AbiRegistry, DecodedParameter },
+    args::NumericEncoding,
+    dex::{
+        decode_uniswap_v2_burn,
+        decode_uniswap_v2_mint,
+        decode_uniswap_v2_swap,
+        decode_uniswap_v2_sync,
+        decode_uniswap_v3_burn,
+        decode_uniswap_v3_mint,
+        decode_uniswap_v3_swap,
+        split_amount,
+        uniswap_v2,
+        uniswap_v3,
+    },
+    quantity::Quantity,
+    requests::{ decode_requests, ElRequest },
+    state::BlockStateChanges,
+    tokens::{
+        decode_erc1155_transfer_batch,
+        decode_erc1155_transfer_single,
+        decode_erc20_event,
+        decode_erc721_transfer,
+        erc1155,
+        erc20,
+    },
+};
+
+// options of the transformation, set with the command-line arguments
+#[derive(Debug, Clone, Default)]
+pub struct TransformOptions {
+    pub signed_transactions: bool,
+    pub numeric_encoding: NumericEncoding,
+    // contracts with known ABIs, their logs are decoded into the decoded-logs dataset
+    pub abi_registry: Option<Arc<AbiRegistry>>,
+}
+
+impl TransformOptions {
+    pub fn quantity(&self, value: U256) -> Quantity {
+        Quantity::new(value, self.numeric_encoding)
+    }
+}
+
+// what the emitter knows about a block besides its body and receipts
+#[derive(Debug)]
+pub struct BlockContext<'a> {
+    pub state_changes: &'a BlockStateChanges,
+    // only when tracing
+    pub traces: Option<&'a Vec<Vec<TransactionTrace>>>,
+    // None after the merge
+    pub total_difficulty: Option<U256>,
+    // None before Cancun
+    pub blob_params: Option<BlobParams>,
+    // reward of the miner before uncle rewards, None after the merge
+    pub base_block_reward: Option<u128>,
+    // EIP-7685 requests from the execution outcome, None if the outcome doesn't carry them
+    pub requests: Option<&'a Requests>,
+}
 
 // structs for serializing
 #[derive(Debug, serde::Serialize)]
@@ -25,13 +91,24 @@ pub struct EmitterBlock {
     pub extra_data: Bytes,
     pub logs_bloom: Bloom,
     pub timestamp: u64,
-    pub difficulty: u64,
+    pub difficulty: Quantity,
+    // None after the merge, where the total difficulty stays at the terminal total difficulty
+    pub total_difficulty: Option<Quantity>,
     pub size: usize,
     pub mix_hash: FixedBytes<32>,
     pub nonce: FixedBytes<8>,
     pub base_fee_per_gas: u64,
     pub withdrawals_root: Option<FixedBytes<32>>,
+    // EIP-4844, None before Cancun
+    pub blob_gas_used: Option<u64>,
+    pub excess_blob_gas: Option<u64>,
+    pub blob_gas_price: Option<Quantity>,
+    pub parent_beacon_block_root: Option<FixedBytes<32>>,
+    // EIP-7685, None before Prague
+    pub requests_hash: Option<FixedBytes<32>>,
     pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -41,33 +118,86 @@ pub struct EmitterTransaction {
     pub transaction_hash: FixedBytes<32>,
     pub nonce: u64,
     pub from_address: Address,
-    pub to_address: Address,
+    // None for contract creations
+    pub to_address: Option<Address>,
+    // same as value, always as decimal string (cryo)
     pub value_string: String,
+    pub value: Quantity,
     pub input: Bytes,
+    // first 4 bytes of the input, None for contract creations and inputs without a selector
+    pub method_selector: Option<FixedBytes<4>>,
     pub gas_limit: u64,
     pub gas_used: u64,
-    pub gas_price: Option<u128>,
+    pub cumulative_gas_used: u64,
+    pub gas_price: Option<Quantity>,
+    pub effective_gas_price: Quantity,
     pub transaction_type: u32,
-    pub max_priority_fee_per_gas: Option<u128>,
-    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: Option<Quantity>,
+    pub max_fee_per_gas: Quantity,
+    // EIP-4844, None for transactions without blobs
+    pub max_fee_per_blob_gas: Option<Quantity>,
+    pub blob_versioned_hashes: Option<Vec<FixedBytes<32>>>,
+    pub blob_gas_used: Option<u64>,
+    pub blob_gas_price: Option<Quantity>,
+    pub blob_fee: Option<Quantity>,
     pub success: bool,
     pub chain_id: u64,
     pub block_hash: FixedBytes<32>,
     pub timestamp: u64,
+    // address of the contract created by the transaction (CREATE), None for calls
+    pub contract_address: Option<Address>,
+    // signature and EIP-2718 encoding, only with --exex-signed-transactions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub v: Option<Quantity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y_parity: Option<bool>,
+    // r and s are opaque 256-bit values, not amounts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r: Option<FixedBytes<32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s: Option<FixedBytes<32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Bytes>,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
 }
 
+// receipt of a transaction, same shape as eth_getTransactionReceipt
 #[derive(Debug, serde::Serialize)]
 pub struct EmitterReceipt {
-    pub success: bool,
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    pub from_address: Address,
+    // None for contract creations
+    pub to_address: Option<Address>,
+    // 1 for success, 0 for failure (EIP-658)
+    pub status: u64,
     pub cumulative_gas_used: u64,
+    pub gas_used: u64,
+    pub effective_gas_price: Quantity,
+    // address of the contract created by the transaction (CREATE), None for calls
+    pub contract_address: Option<Address>,
+    pub logs_bloom: Bloom,
     pub logs: Vec<EmitterLog>,
+    pub transaction_type: u32,
+    // EIP-4844, None for transactions without blobs
+    pub blob_gas_used: Option<u64>,
+    pub blob_gas_price: Option<Quantity>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct EmitterLog {
     pub block_number: u64,
     pub transaction_index: u64,
+    // position of the log in the block, same as logIndex in eth_getLogs and cryo
     pub log_index: u64,
+    // position of the log in the receipt of its transaction
+    pub transaction_log_index: u64,
     pub transaction_hash: FixedBytes<32>,
     pub address: Address,
     pub topic0: FixedBytes<32>,
@@ -77,10 +207,620 @@ pub struct EmitterLog {
     pub data: Bytes,
     pub chain_id: u64,
     pub block_hash: FixedBytes<32>,
+    // true if the log was orphaned by a reorg or revert, same as `removed` in eth_getLogs
+    pub removed: bool,
+}
+
+// EIP-4895 withdrawal from the beacon chain, processed at the end of a block
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterWithdrawal {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub timestamp: u64,
+    // position of the withdrawal in the block
+    pub withdrawal_index: u64,
+    // index of the withdrawal on the beacon chain, increases monotonically across blocks
+    pub index: u64,
+    pub validator_index: u64,
+    pub address: Address,
+    // in gwei
+    pub amount: u64,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// call of a transaction, flattened like Parity's trace_block, from re-executing the block
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterTrace {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // path of the call in the call tree, empty for the top-level call of the transaction
+    pub trace_address: Vec<u64>,
+    pub subtraces: u64,
+    // call, create or suicide
+    pub trace_type: String,
+    // call, callcode, delegatecall or staticcall, None for creates and selfdestructs
+    pub call_type: Option<String>,
+    pub from_address: Address,
+    // created contract for creates, beneficiary for selfdestructs, None for failed creates
+    pub to_address: Option<Address>,
+    pub value: Quantity,
+    // init code for creates
+    pub input: Bytes,
+    // deployed code for creates, None for failed calls and selfdestructs
+    pub output: Option<Bytes>,
+    pub gas: u64,
+    pub gas_used: Option<u64>,
+    // e.g. "Reverted", None for successful calls
+    pub error: Option<String>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// movement of native ETH, from transactions, internal calls, selfdestructs, withdrawals and rewards
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterEthTransfer {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    // position of the transfer in the block
+    pub transfer_index: u64,
+    // None for withdrawals and block rewards
+    pub transaction_index: Option<u64>,
+    pub transaction_hash: Option<FixedBytes<32>>,
+    pub trace_address: Option<Vec<u64>>,
+    // None for withdrawals and block rewards, which mint new ETH
+    pub from_address: Option<Address>,
+    // None for burned fees
+    pub to_address: Option<Address>,
+    pub value: Quantity,
+    // top-level, internal, selfdestruct, fee, withdrawal or block-reward
+    pub kind: String,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// ERC-20 Transfer event, decoded from the logs
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterErc20Transfer {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the log in the block
+    pub log_index: u64,
+    pub token_address: Address,
+    pub from_address: Address,
+    pub to_address: Address,
+    // raw amount, not scaled by the decimals of the token
+    pub amount: Quantity,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// ERC-20 Approval event, decoded from the logs
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterErc20Approval {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the log in the block
+    pub log_index: u64,
+    pub token_address: Address,
+    pub owner_address: Address,
+    pub spender_address: Address,
+    // raw amount, not scaled by the decimals of the token
+    pub amount: Quantity,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// ERC-721 or ERC-1155 transfer, decoded from the logs, one per token of ERC-1155 batches
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterNftTransfer {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the log in the block
+    pub log_index: u64,
+    // position of the token in an ERC-1155 TransferBatch, 0 otherwise
+    pub batch_index: u64,
+    // erc721 or erc1155
+    pub standard: String,
+    pub contract_address: Address,
+    // ERC-1155 only
+    pub operator_address: Option<Address>,
+    pub from_address: Address,
+    pub to_address: Address,
+    pub token_id: Quantity,
+    // always 1 for ERC-721
+    pub amount: Quantity,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// Uniswap V2 or V3 swap, liquidity change or V2 reserve update, decoded from the logs. Amounts
+// are raw token amounts, in and out from the point of view of the pool.
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterDexEvent {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the log in the block
+    pub log_index: u64,
+    // uniswap-v2 or uniswap-v3
+    pub protocol: String,
+    // swap, mint, burn or sync (V2 only)
+    pub event_type: String,
+    pub pool_address: Address,
+    // None for V3 burns and syncs
+    pub sender_address: Option<Address>,
+    // receiver of the tokens of swaps and V2 burns
+    pub recipient_address: Option<Address>,
+    // owner of the V3 position of mints and burns
+    pub owner_address: Option<Address>,
+    // swaps and mints
+    pub amount0_in: Option<Quantity>,
+    pub amount1_in: Option<Quantity>,
+    // swaps and burns
+    pub amount0_out: Option<Quantity>,
+    pub amount1_out: Option<Quantity>,
+    // reserves of the pair after the event, V2 syncs only
+    pub reserve0: Option<Quantity>,
+    pub reserve1: Option<Quantity>,
+    // V3 only: price after a swap (Q64.96), liquidity of the pool after a swap or liquidity added
+    // or removed by mints and burns
+    pub sqrt_price_x96: Option<Quantity>,
+    pub liquidity: Option<Quantity>,
+    pub tick: Option<i32>,
+    pub tick_lower: Option<i32>,
+    pub tick_upper: Option<i32>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// log of a contract with a known ABI, decoded with the matching event of the ABI
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterDecodedLog {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the log in the block
+    pub log_index: u64,
+    pub contract_address: Address,
+    pub event_name: String,
+    // e.g. Transfer(address,address,uint256)
+    pub event_signature: String,
+    // in the order of the event inputs, numbers use the configured encoding
+    pub parameters: Vec<DecodedParameter>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// transaction to a contract with a known ABI, input decoded with the matching function of the ABI
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterDecodedCall {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    pub contract_address: Address,
+    pub method_selector: FixedBytes<4>,
+    pub method_name: String,
+    // e.g. transfer(address,uint256)
+    pub method_signature: String,
+    // in the order of the function inputs, numbers use the configured encoding
+    pub parameters: Vec<DecodedParameter>,
+    // calls of failed transactions are decoded as well
+    pub success: bool,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// logs with the signature of a known event that couldn't be decoded
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SkippedLogs {
+    pub erc20_transfers: u64,
+    pub erc20_approvals: u64,
+    pub nft_transfers: u64,
+    pub dex_events: u64,
+    // logs of contracts with a known ABI that don't match any event of the ABI
+    pub decoded_logs: u64,
+}
+
+impl SkippedLogs {
+    pub fn add(&mut self, other: &SkippedLogs) {
+        self.erc20_transfers += other.erc20_transfers;
+        self.erc20_approvals += other.erc20_approvals;
+        self.nft_transfers += other.nft_transfers;
+        self.dex_events += other.dex_events;
+        self.decoded_logs += other.decoded_logs;
+    }
+}
+
+// decoded token events of a transaction
+#[derive(Debug, Default)]
+pub struct TokenLogs {
+    pub erc20_transfers: Vec<EmitterErc20Transfer>,
+    pub erc20_approvals: Vec<EmitterErc20Approval>,
+    pub nft_transfers: Vec<EmitterNftTransfer>,
+    pub skipped: SkippedLogs,
+}
+
+// EIP-7702 authorization of a set-code transaction (type 4)
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterAuthorization {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    pub authorization_index: u64,
+    // chain id signed in the authorization, 0 is valid on all chains
+    pub authorization_chain_id: Quantity,
+    // contract the authority delegates to
+    pub address: Address,
+    pub nonce: u64,
+    // None if the signature can't be recovered, the EVM skips such authorizations
+    pub authority: Option<Address>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// EIP-2930 access list entry of a transaction, one per storage key
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterAccessListItem {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub transaction_index: u64,
+    pub transaction_hash: FixedBytes<32>,
+    // position of the address in the access list of the transaction
+    pub access_list_index: u64,
+    pub address: Address,
+    // None for addresses without storage keys
+    pub storage_key_index: Option<u64>,
+    pub storage_key: Option<FixedBytes<32>>,
+    pub chain_id: u64,
+    // true if the transaction was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// balance of an account before and after a block, derived from the state changes of the block
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterBalanceDiff {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub address: Address,
+    // 0 for accounts that didn't exist before or don't exist after the block
+    pub previous_balance: Quantity,
+    pub new_balance: Quantity,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// nonce of an account before and after a block, derived from the state changes of the block
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterNonceDiff {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub address: Address,
+    // 0 for accounts that didn't exist before or don't exist after the block
+    pub previous_nonce: u64,
+    pub new_nonce: u64,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// storage slot of an account before and after a block, derived from the state changes of the block
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterStorageDiff {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub address: Address,
+    pub slot: FixedBytes<32>,
+    // 32-byte words, zero for slots that weren't set
+    pub previous_value: FixedBytes<32>,
+    pub new_value: FixedBytes<32>,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// contract deployed in a block, by a transaction (CREATE) or an internal call (CREATE/CREATE2)
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterContract {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub contract_address: Address,
+    // the sender for deployments by transactions, the creating contract for internal CREATE and
+    // CREATE2. Deployer and creation transaction of internal deployments are only known when the
+    // block is traced (traces or eth-transfers dataset), otherwise they are None.
+    pub deployer: Option<Address>,
+    pub transaction_index: Option<u64>,
+    pub transaction_hash: Option<FixedBytes<32>>,
+    pub code_hash: FixedBytes<32>,
+    pub chain_id: u64,
+    // true if the deployment was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// all records of a transaction
+#[derive(Debug)]
+pub struct EmitterTransactionRecords {
+    pub transaction: EmitterTransaction,
+    pub receipt: EmitterReceipt,
+    pub logs: Vec<EmitterLog>,
+    pub authorizations: Vec<EmitterAuthorization>,
+    pub access_list: Vec<EmitterAccessListItem>,
+    // only when tracing
+    pub traces: Vec<EmitterTrace>,
+    pub erc20_transfers: Vec<EmitterErc20Transfer>,
+    pub erc20_approvals: Vec<EmitterErc20Approval>,
+    pub nft_transfers: Vec<EmitterNftTransfer>,
+    pub dex_events: Vec<EmitterDexEvent>,
+    // only with an ABI registry
+    pub decoded_logs: Vec<EmitterDecodedLog>,
+    pub decoded_call: Option<EmitterDecodedCall>,
+    pub skipped_logs: SkippedLogs,
+}
+
+// EIP-7685 execution-layer request of a block: beacon chain deposit (EIP-6110), withdrawal
+// request (EIP-7002) or consolidation request (EIP-7251)
+#[derive(Debug, serde::Serialize)]
+pub struct EmitterElRequest {
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+    pub timestamp: u64,
+    // position of the request in the block, requests are ordered by type
+    pub request_index: u64,
+    // deposit, withdrawal or consolidation
+    pub request_type: String,
+    // withdrawals and consolidations, the account that sent the request to the system contract
+    pub source_address: Option<Address>,
+    // validator of deposits and withdrawals, source validator of consolidations
+    pub validator_pubkey: Bytes,
+    // consolidations only
+    pub target_pubkey: Option<Bytes>,
+    // deposits only
+    pub withdrawal_credentials: Option<FixedBytes<32>>,
+    pub signature: Option<Bytes>,
+    pub deposit_index: Option<u64>,
+    // in gwei, None for consolidations, 0 for full exits
+    pub amount: Option<u64>,
+    pub chain_id: u64,
+    // true if the block was orphaned by a reorg or revert (retraction record)
+    pub removed: bool,
+}
+
+// all records of a block
+#[derive(Debug)]
+pub struct EmitterBlockRecords {
+    pub block: EmitterBlock,
+    pub transactions: Vec<EmitterTransactionRecords>,
+    pub withdrawals: Vec<EmitterWithdrawal>,
+    pub eth_transfers: Vec<EmitterEthTransfer>,
+    pub balance_diffs: Vec<EmitterBalanceDiff>,
+    pub nonce_diffs: Vec<EmitterNonceDiff>,
+    pub storage_diffs: Vec<EmitterStorageDiff>,
+    pub contracts: Vec<EmitterContract>,
+    pub el_requests: Vec<EmitterElRequest>,
+}
+
+// last block that was fully published, stored in the compacted <prefix>-cursor topic
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct EmitterCursor {
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub block_hash: FixedBytes<32>,
+}
+
+// a record of a dataset, published to the <prefix>-<dataset> topic
+pub trait Record: serde::Serialize {
+    const DATASET: &'static str;
+
+    fn key(&self) -> String;
+}
+
+impl Record for EmitterBlock {
+    const DATASET: &'static str = "blocks";
+
+    fn key(&self) -> String {
+        // self.block_number.to_string()
+        "".to_string()
+    }
+}
+
+impl Record for EmitterTransaction {
+    const DATASET: &'static str = "transactions";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.transaction_index)
+    }
+}
+
+impl Record for EmitterReceipt {
+    const DATASET: &'static str = "receipts";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.transaction_index)
+    }
+}
+
+impl Record for EmitterLog {
+    const DATASET: &'static str = "logs";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.log_index)
+    }
+}
+
+impl Record for EmitterWithdrawal {
+    const DATASET: &'static str = "withdrawals";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.withdrawal_index)
+    }
+}
+
+impl Record for EmitterTrace {
+    const DATASET: &'static str = "traces";
+
+    fn key(&self) -> String {
+        let trace_address: Vec<String> = self.trace_address
+            .iter()
+            .map(|index| index.to_string())
+            .collect();
+        format!("{}-{}-{}", self.block_number, self.transaction_index, trace_address.join("_"))
+    }
+}
+
+impl Record for EmitterEthTransfer {
+    const DATASET: &'static str = "eth-transfers";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.transfer_index)
+    }
+}
+
+impl Record for EmitterErc20Transfer {
+    const DATASET: &'static str = "erc20-transfers";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.log_index)
+    }
+}
+
+impl Record for EmitterErc20Approval {
+    const DATASET: &'static str = "erc20-approvals";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.log_index)
+    }
+}
+
+impl Record for EmitterNftTransfer {
+    const DATASET: &'static str = "nft-transfers";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}-{}", self.block_number, self.transaction_index, self.log_index, self.batch_index)
+    }
+}
+
+impl Record for EmitterDexEvent {
+    const DATASET: &'static str = "dex-swaps";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.log_index)
+    }
+}
+
+impl Record for EmitterDecodedLog {
+    const DATASET: &'static str = "decoded-logs";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.log_index)
+    }
+}
+
+impl Record for EmitterDecodedCall {
+    const DATASET: &'static str = "decoded-calls";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.transaction_index)
+    }
+}
+
+impl Record for EmitterAuthorization {
+    const DATASET: &'static str = "authorizations";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.transaction_index, self.authorization_index)
+    }
+}
+
+impl Record for EmitterAccessListItem {
+    const DATASET: &'static str = "access-lists";
+
+    fn key(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            self.block_number,
+            self.transaction_index,
+            self.access_list_index,
+            self.storage_key_index.map(|index| index.to_string()).unwrap_or_default()
+        )
+    }
+}
+
+impl Record for EmitterBalanceDiff {
+    const DATASET: &'static str = "balance-diffs";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.address)
+    }
+}
+
+impl Record for EmitterNonceDiff {
+    const DATASET: &'static str = "nonce-diffs";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.address)
+    }
+}
+
+impl Record for EmitterStorageDiff {
+    const DATASET: &'static str = "storage-diffs";
+
+    fn key(&self) -> String {
+        format!("{}-{}-{}", self.block_number, self.address, self.slot)
+    }
+}
+
+impl Record for EmitterElRequest {
+    const DATASET: &'static str = "el-requests";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.request_index)
+    }
+}
+
+impl Record for EmitterContract {
+    const DATASET: &'static str = "contracts";
+
+    fn key(&self) -> String {
+        format!("{}-{}", self.block_number, self.contract_address)
+    }
+}
+
+impl Record for EmitterCursor {
+    const DATASET: &'static str = "cursor";
+
+    fn key(&self) -> String {
+        self.chain_id.to_string()
+    }
 }
 
 pub fn process_committed_block(
     block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    total_difficulty: Option<U256>,
+    blob_params: Option<BlobParams>,
+    options: &TransformOptions,
     chain_id: u64
 ) -> EmitterBlock {
     EmitterBlock {
@@ -96,43 +836,188 @@ pub fn process_committed_block(
         extra_data: block.extra_data.clone(),
         logs_bloom: block.logs_bloom(),
         timestamp: block.timestamp(),
-        difficulty: block.difficulty.to::<u64>(),
+        difficulty: options.quantity(block.difficulty),
+        total_difficulty: total_difficulty.map(|total_difficulty| options.quantity(total_difficulty)),
         size: block.size(),
         mix_hash: block.mix_hash().unwrap(),
         nonce: block.nonce,
         base_fee_per_gas: block.base_fee_per_gas.unwrap_or_default(),
         withdrawals_root: block.withdrawals_root,
+        blob_gas_used: block.blob_gas_used,
+        excess_blob_gas: block.excess_blob_gas,
+        blob_gas_price: blob_params
+            .and_then(|blob_params| block.header().blob_fee(blob_params))
+            .map(|blob_gas_price| options.quantity(U256::from(blob_gas_price))),
+        parent_beacon_block_root: block.parent_beacon_block_root,
+        requests_hash: block.requests_hash,
         chain_id: chain_id,
+        removed: false,
     }
 }
 
 pub fn process_block_with_receipts(
     block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<reth::primitives::TransactionSigned>>,
     receipts: &Vec<reth::primitives::Receipt>,
+    context: &BlockContext,
+    options: &TransformOptions,
     chain_id: u64
-) -> (EmitterBlock, Vec<(EmitterTransaction, Vec<EmitterLog>)>) {
+) -> EmitterBlockRecords {
+    let state_changes = context.state_changes;
+
     // block
-    let emitter_block = process_committed_block(block, chain_id);
+    let emitter_block = process_committed_block(
+        block,
+        context.total_difficulty,
+        context.blob_params,
+        options,
+        chain_id
+    );
 
     // transactions + logs
-    let emitter_transactions = process_transactions_in_block(block, receipts, chain_id);
+    let blob_gas_price = context.blob_params.and_then(|blob_params| block.header().blob_fee(blob_params));
+    let emitter_transactions = process_transactions_in_block(
+        block,
+        receipts,
+        context.traces,
+        blob_gas_price,
+        options,
+        chain_id
+    );
+
+    // withdrawals
+    let emitter_withdrawals = process_withdrawals_in_block(block, chain_id);
+
+    // eth transfers
+    let emitter_eth_transfers = process_eth_transfers_in_block(
+        block,
+        receipts,
+        context,
+        blob_gas_price,
+        options,
+        chain_id
+    );
+
+    // balance + nonce diffs
+    let emitter_balance_diffs = process_balance_diffs_in_block(block, state_changes, options, chain_id);
+    let emitter_nonce_diffs = process_nonce_diffs_in_block(block, state_changes, chain_id);
 
-    (emitter_block, emitter_transactions)
+    // storage diffs
+    let emitter_storage_diffs = process_storage_diffs_in_block(block, state_changes, chain_id);
+
+    // contracts
+    let emitter_contracts = process_contracts_in_block(
+        block,
+        state_changes,
+        &emitter_transactions,
+        context.traces,
+        chain_id
+    );
+
+    // execution-layer requests
+    let emitter_el_requests = context.requests
+        .map(|requests| process_el_requests_in_block(block, requests, chain_id))
+        .unwrap_or_default();
+
+    EmitterBlockRecords {
+        block: emitter_block,
+        transactions: emitter_transactions,
+        withdrawals: emitter_withdrawals,
+        eth_transfers: emitter_eth_transfers,
+        balance_diffs: emitter_balance_diffs,
+        nonce_diffs: emitter_nonce_diffs,
+        storage_diffs: emitter_storage_diffs,
+        contracts: emitter_contracts,
+        el_requests: emitter_el_requests,
+    }
+}
+
+// Same as process_block_with_receipts, but marks all records as removed. Retraction records
+// carry the same key and payload as the originals, so consumers can undo them after a reorg.
+pub fn retract_block_with_receipts(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<reth::primitives::TransactionSigned>>,
+    receipts: &Vec<reth::primitives::Receipt>,
+    context: &BlockContext,
+    options: &TransformOptions,
+    chain_id: u64
+) -> EmitterBlockRecords {
+    let mut records = process_block_with_receipts(block, receipts, context, options, chain_id);
+
+    records.block.removed = true;
+    records.transactions.iter_mut().for_each(|transaction_records| {
+        transaction_records.transaction.removed = true;
+        transaction_records.receipt.removed = true;
+        transaction_records.receipt.logs.iter_mut().for_each(|emitter_log| emitter_log.removed = true);
+        transaction_records.logs.iter_mut().for_each(|emitter_log| emitter_log.removed = true);
+        transaction_records.authorizations
+            .iter_mut()
+            .for_each(|emitter_authorization| emitter_authorization.removed = true);
+        transaction_records.access_list
+            .iter_mut()
+            .for_each(|emitter_access_list_item| emitter_access_list_item.removed = true);
+        transaction_records.traces.iter_mut().for_each(|emitter_trace| emitter_trace.removed = true);
+        transaction_records.erc20_transfers
+            .iter_mut()
+            .for_each(|emitter_erc20_transfer| emitter_erc20_transfer.removed = true);
+        transaction_records.erc20_approvals
+            .iter_mut()
+            .for_each(|emitter_erc20_approval| emitter_erc20_approval.removed = true);
+        transaction_records.nft_transfers
+            .iter_mut()
+            .for_each(|emitter_nft_transfer| emitter_nft_transfer.removed = true);
+        transaction_records.dex_events
+            .iter_mut()
+            .for_each(|emitter_dex_event| emitter_dex_event.removed = true);
+        transaction_records.decoded_logs
+            .iter_mut()
+            .for_each(|emitter_decoded_log| emitter_decoded_log.removed = true);
+        transaction_records.decoded_call
+            .iter_mut()
+            .for_each(|emitter_decoded_call| emitter_decoded_call.removed = true);
+    });
+    records.withdrawals.iter_mut().for_each(|emitter_withdrawal| emitter_withdrawal.removed = true);
+    records.eth_transfers.iter_mut().for_each(|emitter_eth_transfer| emitter_eth_transfer.removed = true);
+    records.balance_diffs.iter_mut().for_each(|emitter_balance_diff| emitter_balance_diff.removed = true);
+    records.nonce_diffs.iter_mut().for_each(|emitter_nonce_diff| emitter_nonce_diff.removed = true);
+    records.storage_diffs.iter_mut().for_each(|emitter_storage_diff| emitter_storage_diff.removed = true);
+    records.contracts.iter_mut().for_each(|emitter_contract| emitter_contract.removed = true);
+    records.el_requests.iter_mut().for_each(|emitter_el_request| emitter_el_request.removed = true);
+
+    records
 }
 
 pub fn process_transactions_in_block(
     block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
     receipts: &Vec<Receipt>,
+    traces: Option<&Vec<Vec<TransactionTrace>>>,
+    blob_gas_price: Option<u128>,
+    options: &TransformOptions,
     chain_id: u64
-) -> Vec<(EmitterTransaction, Vec<EmitterLog>)> {
+) -> Vec<EmitterTransactionRecords> {
+    let mut block_log_index: u64 = 0;
+
     let transactions = block
         .transactions_with_sender()
         .enumerate()
         .map(|(tx_index, (sender, transaction))| {
             let tx_hash = transaction.hash();
 
+            // receipts only carry the cumulative gas of the block up to this transaction
+            let cumulative_gas_used = receipts[tx_index].cumulative_gas_used;
+            let previous_cumulative_gas_used = match tx_index {
+                0 => 0,
+                _ => receipts[tx_index - 1].cumulative_gas_used,
+            };
+
             let logs = receipts[tx_index].logs.clone();
 
+            let first_log_index = block_log_index;
+            block_log_index += logs.len() as u64;
+
+            // every blob uses the same fixed amount of blob gas
+            let blob_gas_used = transaction
+                .blob_versioned_hashes()
+                .map(|hashes| (hashes.len() as u64) * DATA_GAS_PER_BLOB);
+
             let emitter_logs: Vec<EmitterLog> = logs
                 .iter()
                 .enumerate()
@@ -142,7 +1027,8 @@ pub fn process_transactions_in_block(
                         block_number: block.number,
                         transaction_hash: *tx_hash,
                         transaction_index: tx_index as u64,
-                        log_index: log_index as u64,
+                        log_index: first_log_index + log_index as u64,
+                        transaction_log_index: log_index as u64,
                         address: log.address,
                         topic0: log.topics().get(0).copied().unwrap_or_default(),
                         topic1: log.topics().get(1).copied().unwrap_or_default(),
@@ -150,33 +1036,980 @@ pub fn process_transactions_in_block(
                         topic3: log.topics().get(3).copied().unwrap_or_default(),
                         data: log.data.data.clone(),
                         chain_id: chain_id,
+                        removed: false,
                     }
                 })
                 .collect();
 
+            let signature = transaction.signature();
+            let signed = options.signed_transactions;
+
             let emitter_transaction = EmitterTransaction {
                 transaction_hash: tx_hash.clone(),
                 nonce: transaction.nonce(),
                 input: transaction.input().clone(),
+                method_selector: transaction
+                    .to()
+                    .and_then(|_| transaction.input().get(..4))
+                    .map(FixedBytes::<4>::from_slice),
                 block_hash: block.hash(),
                 block_number: block.number,
                 timestamp: block.timestamp(),
                 transaction_index: tx_index as u64,
                 from_address: sender.clone(),
-                to_address: transaction.to().unwrap_or_default(),
+                to_address: transaction.to(),
                 value_string: transaction.value().to_string(),
+                value: options.quantity(transaction.value()),
                 gas_limit: transaction.gas_limit(),
-                gas_price: transaction.gas_price(),
-                max_fee_per_gas: transaction.max_fee_per_gas(),
-                max_priority_fee_per_gas: transaction.max_priority_fee_per_gas(),
+                gas_price: transaction.gas_price().map(|gas_price| options.quantity(U256::from(gas_price))),
+                effective_gas_price: options.quantity(
+                    U256::from(transaction.effective_gas_price(block.base_fee_per_gas))
+                ),
+                max_fee_per_gas: options.quantity(U256::from(transaction.max_fee_per_gas())),
+                max_priority_fee_per_gas: transaction
+                    .max_priority_fee_per_gas()
+                    .map(|max_priority_fee_per_gas| options.quantity(U256::from(max_priority_fee_per_gas))),
+                max_fee_per_blob_gas: transaction
+                    .max_fee_per_blob_gas()
+                    .map(|max_fee_per_blob_gas| options.quantity(U256::from(max_fee_per_blob_gas))),
+                blob_versioned_hashes: transaction.blob_versioned_hashes().map(|hashes| hashes.to_vec()),
+                blob_gas_used,
+                blob_gas_price: blob_gas_used
+                    .and(blob_gas_price)
+                    .map(|blob_gas_price| options.quantity(U256::from(blob_gas_price))),
+                blob_fee: blob_gas_used
+                    .zip(blob_gas_price)
+                    .map(|(gas, price)| options.quantity(U256::from(gas) * U256::from(price))),
                 transaction_type: transaction.tx_type() as u32,
                 success: receipts[tx_index].status(),
-                gas_used: receipts[tx_index].cumulative_gas_used,
+                gas_used: cumulative_gas_used - previous_cumulative_gas_used,
+                cumulative_gas_used,
                 chain_id: chain_id,
+                contract_address: match transaction.is_create() {
+                    true => Some(sender.create(transaction.nonce())),
+                    false => None,
+                },
+                // legacy transactions encode the chain id in v (EIP-155), typed transactions the parity
+                v: signed.then(|| match transaction.tx_type() {
+                    TxType::Legacy => {
+                        options.quantity(U256::from(to_eip155_value(signature.v(), transaction.chain_id())))
+                    }
+                    _ => options.quantity(U256::from(signature.v() as u8)),
+                }),
+                y_parity: signed.then(|| signature.v()),
+                r: signed.then(|| FixedBytes::from(signature.r())),
+                s: signed.then(|| FixedBytes::from(signature.s())),
+                raw: signed.then(|| Bytes::from(transaction.encoded_2718())),
+                removed: false,
             };
-            (emitter_transaction, emitter_logs)
+
+            let emitter_receipt = EmitterReceipt {
+                block_number: block.number,
+                block_hash: block.hash(),
+                transaction_index: tx_index as u64,
+                transaction_hash: *tx_hash,
+                from_address: emitter_transaction.from_address,
+                to_address: emitter_transaction.to_address,
+                status: receipts[tx_index].status() as u64,
+                cumulative_gas_used,
+                gas_used: emitter_transaction.gas_used,
+                effective_gas_price: emitter_transaction.effective_gas_price,
+                contract_address: emitter_transaction.contract_address,
+                logs_bloom: receipts[tx_index].bloom(),
+                logs: emitter_logs.clone(),
+                transaction_type: emitter_transaction.transaction_type,
+                blob_gas_used,
+                blob_gas_price: emitter_transaction.blob_gas_price,
+                chain_id: chain_id,
+                removed: false,
+            };
+
+            let emitter_authorizations: Vec<EmitterAuthorization> = transaction
+                .authorization_list()
+                .unwrap_or_default()
+                .iter()
+                .enumerate()
+                .map(|(authorization_index, authorization)| {
+                    EmitterAuthorization {
+                        block_number: block.number,
+                        block_hash: block.hash(),
+                        transaction_index: tx_index as u64,
+                        transaction_hash: *tx_hash,
+                        authorization_index: authorization_index as u64,
+                        authorization_chain_id: options.quantity(authorization.chain_id),
+                        address: authorization.address,
+                        nonce: authorization.nonce,
+                        authority: authorization.recover_authority().ok(),
+                        chain_id: chain_id,
+                        removed: false,
+                    }
+                })
+                .collect();
+
+            let emitter_access_list: Vec<EmitterAccessListItem> = transaction
+                .access_list()
+                .map(|access_list| access_list.iter().collect::<Vec<_>>())
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+                .flat_map(|(access_list_index, item)| {
+                    let access_list_item = |storage_key_index: Option<usize>| EmitterAccessListItem {
+                        block_number: block.number,
+                        block_hash: block.hash(),
+                        transaction_index: tx_index as u64,
+                        transaction_hash: *tx_hash,
+                        access_list_index: access_list_index as u64,
+                        address: item.address,
+                        storage_key_index: storage_key_index.map(|index| index as u64),
+                        storage_key: storage_key_index.map(|index| item.storage_keys[index]),
+                        chain_id: chain_id,
+                        removed: false,
+                    };
+
+                    // keep addresses without storage keys, they are pre-warmed as well
+                    match item.storage_keys.len() {
+                        0 => vec![access_list_item(None)],
+                        len => (0..len).map(|index| access_list_item(Some(index))).collect(),
+                    }
+                })
+                .collect();
+
+            let emitter_traces: Vec<EmitterTrace> = traces
+                .and_then(|traces| traces.get(tx_index))
+                .map(|transaction_traces| {
+                    process_transaction_traces(block, tx_index, *tx_hash, transaction_traces, options, chain_id)
+                })
+                .unwrap_or_default();
+
+            let mut token_logs = process_token_logs(&logs, &emitter_logs, options);
+            let (emitter_dex_events, skipped_dex_events) = process_dex_logs(&logs, &emitter_logs, options);
+            token_logs.skipped.dex_events = skipped_dex_events;
+            let (emitter_decoded_logs, skipped_decoded_logs) = options.abi_registry
+                .as_ref()
+                .map(|abi_registry| process_decoded_logs(abi_registry, &logs, &emitter_logs, options))
+                .unwrap_or_default();
+            token_logs.skipped.decoded_logs = skipped_decoded_logs;
+
+            let emitter_decoded_call = options.abi_registry
+                .as_ref()
+                .and_then(|abi_registry| process_decoded_call(abi_registry, &emitter_transaction, options));
+
+            EmitterTransactionRecords {
+                transaction: emitter_transaction,
+                receipt: emitter_receipt,
+                logs: emitter_logs,
+                authorizations: emitter_authorizations,
+                access_list: emitter_access_list,
+                traces: emitter_traces,
+                erc20_transfers: token_logs.erc20_transfers,
+                erc20_approvals: token_logs.erc20_approvals,
+                nft_transfers: token_logs.nft_transfers,
+                dex_events: emitter_dex_events,
+                decoded_logs: emitter_decoded_logs,
+                decoded_call: emitter_decoded_call,
+                skipped_logs: token_logs.skipped,
+            }
         })
         .collect();
 
     transactions
 }
+
+// Decodes the input of a transaction to a contract of the registry. None for transfers without
+// input and selectors that aren't part of the ABI, e.g. calls to the fallback function.
+pub fn process_decoded_call(
+    abi_registry: &AbiRegistry,
+    emitter_transaction: &EmitterTransaction,
+    options: &TransformOptions
+) -> Option<EmitterDecodedCall> {
+    let contract_address = emitter_transaction.to_address?;
+    let method_selector = emitter_transaction.method_selector?;
+    let call = abi_registry.decode_call(&contract_address, &emitter_transaction.input, options.numeric_encoding)?;
+
+    Some(EmitterDecodedCall {
+        block_number: emitter_transaction.block_number,
+        block_hash: emitter_transaction.block_hash,
+        transaction_index: emitter_transaction.transaction_index,
+        transaction_hash: emitter_transaction.transaction_hash,
+        contract_address,
+        method_selector,
+        method_name: call.name,
+        method_signature: call.signature,
+        parameters: call.parameters,
+        success: emitter_transaction.success,
+        chain_id: emitter_transaction.chain_id,
+        removed: false,
+    })
+}
+
+// Decodes the logs of a transaction emitted by contracts of the registry. Returns the decoded logs
+// and the number of logs that don't match any event of the ABI of their contract.
+pub fn process_decoded_logs(
+    abi_registry: &AbiRegistry,
+    logs: &Vec<alloy::primitives::Log>,
+    emitter_logs: &Vec<EmitterLog>,
+    options: &TransformOptions
+) -> (Vec<EmitterDecodedLog>, u64) {
+    let mut decoded_logs = Vec::new();
+    let mut skipped = 0;
+
+    for (log, emitter_log) in logs.iter().zip(emitter_logs.iter()) {
+        if !abi_registry.contains(&log.address) {
+            continue;
+        }
+
+        match abi_registry.decode(log, options.numeric_encoding) {
+            Some(event) => {
+                decoded_logs.push(EmitterDecodedLog {
+                    block_number: emitter_log.block_number,
+                    block_hash: emitter_log.block_hash,
+                    transaction_index: emitter_log.transaction_index,
+                    transaction_hash: emitter_log.transaction_hash,
+                    log_index: emitter_log.log_index,
+                    contract_address: log.address,
+                    event_name: event.name,
+                    event_signature: event.signature,
+                    parameters: event.parameters,
+                    chain_id: emitter_log.chain_id,
+                    removed: false,
+                });
+            }
+            None => skipped += 1,
+        }
+    }
+
+    (decoded_logs, skipped)
+}
+
+// Decodes the standard token events of a transaction. ERC-20 and ERC-721 transfers share the
+// signature, ERC-721 has the token id as third indexed topic instead of the amount as data.
+pub fn process_token_logs(
+    logs: &Vec<alloy::primitives::Log>,
+    emitter_logs: &Vec<EmitterLog>,
+    options: &TransformOptions
+) -> TokenLogs {
+    let mut token_logs = TokenLogs::default();
+
+    for (log, emitter_log) in logs.iter().zip(emitter_logs.iter()) {
+        let nft_transfer = |batch_index: usize, standard: &str, operator, from, to, token_id, amount| {
+            EmitterNftTransfer {
+                block_number: emitter_log.block_number,
+                block_hash: emitter_log.block_hash,
+                transaction_index: emitter_log.transaction_index,
+                transaction_hash: emitter_log.transaction_hash,
+                log_index: emitter_log.log_index,
+                batch_index: batch_index as u64,
+                standard: standard.to_string(),
+                contract_address: log.address,
+                operator_address: operator,
+                from_address: from,
+                to_address: to,
+                token_id: options.quantity(token_id),
+                amount: options.quantity(amount),
+                chain_id: emitter_log.chain_id,
+                removed: false,
+            }
+        };
+
+        match log.topics().first() {
+            Some(&erc20::Transfer::SIGNATURE_HASH) if log.topics().len() == 4 => {
+                match decode_erc721_transfer(log) {
+                    Some(transfer) => {
+                        token_logs.nft_transfers.push(
+                            nft_transfer(0, "erc721", None, transfer.from, transfer.to, transfer.tokenId, U256::from(1))
+                        );
+                    }
+                    None => {
+                        token_logs.skipped.nft_transfers += 1;
+                    }
+                }
+            }
+            Some(&erc20::Transfer::SIGNATURE_HASH) => {
+                match decode_erc20_event::<erc20::Transfer>(log) {
+                    Some(transfer) => {
+                        token_logs.erc20_transfers.push(EmitterErc20Transfer {
+                            block_number: emitter_log.block_number,
+                            block_hash: emitter_log.block_hash,
+                            transaction_index: emitter_log.transaction_index,
+                            transaction_hash: emitter_log.transaction_hash,
+                            log_index: emitter_log.log_index,
+                            token_address: log.address,
+                            from_address: transfer.from,
+                            to_address: transfer.to,
+                            amount: options.quantity(transfer.value),
+                            chain_id: emitter_log.chain_id,
+                            removed: false,
+                        })
+                    }
+                    None => {
+                        token_logs.skipped.erc20_transfers += 1;
+                    }
+                }
+            }
+            // ERC-721 approvals index the token id, they are not ERC-20 approvals
+            Some(&erc20::Approval::SIGNATURE_HASH) if log.topics().len() != 4 => {
+                match decode_erc20_event::<erc20::Approval>(log) {
+                    Some(approval) => {
+                        token_logs.erc20_approvals.push(EmitterErc20Approval {
+                            block_number: emitter_log.block_number,
+                            block_hash: emitter_log.block_hash,
+                            transaction_index: emitter_log.transaction_index,
+                            transaction_hash: emitter_log.transaction_hash,
+                            log_index: emitter_log.log_index,
+                            token_address: log.address,
+                            owner_address: approval.owner,
+                            spender_address: approval.spender,
+                            amount: options.quantity(approval.value),
+                            chain_id: emitter_log.chain_id,
+                            removed: false,
+                        })
+                    }
+                    None => {
+                        token_logs.skipped.erc20_approvals += 1;
+                    }
+                }
+            }
+            Some(&erc1155::TransferSingle::SIGNATURE_HASH) => {
+                match decode_erc1155_transfer_single(log) {
+                    Some(transfer) => {
+                        token_logs.nft_transfers.push(
+                            nft_transfer(
+                                0,
+                                "erc1155",
+                                Some(transfer.operator),
+                                transfer.from,
+                                transfer.to,
+                                transfer.id,
+                                transfer.value
+                            )
+                        );
+                    }
+                    None => {
+                        token_logs.skipped.nft_transfers += 1;
+                    }
+                }
+            }
+            Some(&erc1155::TransferBatch::SIGNATURE_HASH) => {
+                match decode_erc1155_transfer_batch(log) {
+                    Some(batch) => {
+                        for (batch_index, (id, value)) in batch.ids.iter().zip(batch.values.iter()).enumerate() {
+                            token_logs.nft_transfers.push(
+                                nft_transfer(
+                                    batch_index,
+                                    "erc1155",
+                                    Some(batch.operator),
+                                    batch.from,
+                                    batch.to,
+                                    *id,
+                                    *value
+                                )
+                            );
+                        }
+                    }
+                    None => {
+                        token_logs.skipped.nft_transfers += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    token_logs
+}
+
+// Decodes the Uniswap V2 and V3 pool events of a transaction. Any contract can emit them, the
+// pools are not checked against the factories. Returns the events and the number of malformed logs.
+pub fn process_dex_logs(
+    logs: &Vec<alloy::primitives::Log>,
+    emitter_logs: &Vec<EmitterLog>,
+    options: &TransformOptions
+) -> (Vec<EmitterDexEvent>, u64) {
+    let mut dex_events = Vec::new();
+    let mut skipped = 0;
+
+    for (log, emitter_log) in logs.iter().zip(emitter_logs.iter()) {
+        let dex_event = |protocol: &str, event_type: &str| EmitterDexEvent {
+            block_number: emitter_log.block_number,
+            block_hash: emitter_log.block_hash,
+            transaction_index: emitter_log.transaction_index,
+            transaction_hash: emitter_log.transaction_hash,
+            log_index: emitter_log.log_index,
+            protocol: protocol.to_string(),
+            event_type: event_type.to_string(),
+            pool_address: log.address,
+            sender_address: None,
+            recipient_address: None,
+            owner_address: None,
+            amount0_in: None,
+            amount1_in: None,
+            amount0_out: None,
+            amount1_out: None,
+            reserve0: None,
+            reserve1: None,
+            sqrt_price_x96: None,
+            liquidity: None,
+            tick: None,
+            tick_lower: None,
+            tick_upper: None,
+            chain_id: emitter_log.chain_id,
+            removed: false,
+        };
+        let quantity = |value: U256| Some(options.quantity(value));
+
+        let decoded = match log.topics().first() {
+            Some(&uniswap_v2::Swap::SIGNATURE_HASH) => {
+                decode_uniswap_v2_swap(log).map(|swap| EmitterDexEvent {
+                    sender_address: Some(swap.sender),
+                    recipient_address: Some(swap.to),
+                    amount0_in: quantity(swap.amount0In),
+                    amount1_in: quantity(swap.amount1In),
+                    amount0_out: quantity(swap.amount0Out),
+                    amount1_out: quantity(swap.amount1Out),
+                    ..dex_event("uniswap-v2", "swap")
+                })
+            }
+            Some(&uniswap_v2::Mint::SIGNATURE_HASH) => {
+                decode_uniswap_v2_mint(log).map(|mint| EmitterDexEvent {
+                    sender_address: Some(mint.sender),
+                    amount0_in: quantity(mint.amount0),
+                    amount1_in: quantity(mint.amount1),
+                    ..dex_event("uniswap-v2", "mint")
+                })
+            }
+            Some(&uniswap_v2::Burn::SIGNATURE_HASH) => {
+                decode_uniswap_v2_burn(log).map(|burn| EmitterDexEvent {
+                    sender_address: Some(burn.sender),
+                    recipient_address: Some(burn.to),
+                    amount0_out: quantity(burn.amount0),
+                    amount1_out: quantity(burn.amount1),
+                    ..dex_event("uniswap-v2", "burn")
+                })
+            }
+            Some(&uniswap_v2::Sync::SIGNATURE_HASH) => {
+                decode_uniswap_v2_sync(log).map(|sync| EmitterDexEvent {
+                    reserve0: quantity(U256::from(sync.reserve0)),
+                    reserve1: quantity(U256::from(sync.reserve1)),
+                    ..dex_event("uniswap-v2", "sync")
+                })
+            }
+            Some(&uniswap_v3::Swap::SIGNATURE_HASH) => {
+                decode_uniswap_v3_swap(log).map(|swap| {
+                    let (amount0_in, amount0_out) = split_amount(swap.amount0);
+                    let (amount1_in, amount1_out) = split_amount(swap.amount1);
+
+                    EmitterDexEvent {
+                        sender_address: Some(swap.sender),
+                        recipient_address: Some(swap.recipient),
+                        amount0_in: quantity(amount0_in),
+                        amount1_in: quantity(amount1_in),
+                        amount0_out: quantity(amount0_out),
+                        amount1_out: quantity(amount1_out),
+                        sqrt_price_x96: quantity(U256::from(swap.sqrtPriceX96)),
+                        liquidity: quantity(U256::from(swap.liquidity)),
+                        tick: i32::try_from(swap.tick).ok(),
+                        ..dex_event("uniswap-v3", "swap")
+                    }
+                })
+            }
+            Some(&uniswap_v3::Mint::SIGNATURE_HASH) => {
+                decode_uniswap_v3_mint(log).map(|mint| EmitterDexEvent {
+                    sender_address: Some(mint.sender),
+                    owner_address: Some(mint.owner),
+                    amount0_in: quantity(mint.amount0),
+                    amount1_in: quantity(mint.amount1),
+                    liquidity: quantity(U256::from(mint.amount)),
+                    tick_lower: i32::try_from(mint.tickLower).ok(),
+                    tick_upper: i32::try_from(mint.tickUpper).ok(),
+                    ..dex_event("uniswap-v3", "mint")
+                })
+            }
+            Some(&uniswap_v3::Burn::SIGNATURE_HASH) => {
+                decode_uniswap_v3_burn(log).map(|burn| EmitterDexEvent {
+                    owner_address: Some(burn.owner),
+                    amount0_out: quantity(burn.amount0),
+                    amount1_out: quantity(burn.amount1),
+                    liquidity: quantity(U256::from(burn.amount)),
+                    tick_lower: i32::try_from(burn.tickLower).ok(),
+                    tick_upper: i32::try_from(burn.tickUpper).ok(),
+                    ..dex_event("uniswap-v3", "burn")
+                })
+            }
+            _ => continue,
+        };
+
+        match decoded {
+            Some(emitter_dex_event) => dex_events.push(emitter_dex_event),
+            None => skipped += 1,
+        }
+    }
+
+    (dex_events, skipped)
+}
+
+// flat traces of a transaction, block rewards are not part of the traces of transactions
+pub fn process_transaction_traces(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    tx_index: usize,
+    tx_hash: FixedBytes<32>,
+    transaction_traces: &Vec<TransactionTrace>,
+    options: &TransformOptions,
+    chain_id: u64
+) -> Vec<EmitterTrace> {
+    transaction_traces
+        .iter()
+        .filter_map(|trace| {
+            let gas_used = trace.result.as_ref().map(|result| match result {
+                TraceOutput::Call(output) => output.gas_used,
+                TraceOutput::Create(output) => output.gas_used,
+            });
+
+            let (trace_type, call_type, from_address, to_address, value, input, output, gas) = match &trace.action {
+                Action::Call(call) => {
+                    let output = match &trace.result {
+                        Some(TraceOutput::Call(output)) => Some(output.output.clone()),
+                        _ => None,
+                    };
+                    (
+                        "call",
+                        call_type_name(call.call_type),
+                        call.from,
+                        Some(call.to),
+                        call.value,
+                        call.input.clone(),
+                        output,
+                        call.gas,
+                    )
+                }
+                Action::Create(create) => {
+                    let (address, code) = match &trace.result {
+                        Some(TraceOutput::Create(output)) => (Some(output.address), Some(output.code.clone())),
+                        _ => (None, None),
+                    };
+                    ("create", None, create.from, address, create.value, create.init.clone(), code, create.gas)
+                }
+                Action::Selfdestruct(selfdestruct) => {
+                    (
+                        "suicide",
+                        None,
+                        selfdestruct.address,
+                        Some(selfdestruct.refund_address),
+                        selfdestruct.balance,
+                        Bytes::new(),
+                        None,
+                        0,
+                    )
+                }
+                Action::Reward(_) => {
+                    return None;
+                }
+            };
+
+            Some(EmitterTrace {
+                block_number: block.number,
+                block_hash: block.hash(),
+                transaction_index: tx_index as u64,
+                transaction_hash: tx_hash,
+                trace_address: trace.trace_address
+                    .iter()
+                    .map(|index| *index as u64)
+                    .collect(),
+                subtraces: trace.subtraces as u64,
+                trace_type: trace_type.to_string(),
+                call_type: call_type.map(|call_type| call_type.to_string()),
+                from_address,
+                to_address,
+                value: options.quantity(value),
+                input,
+                output,
+                gas,
+                gas_used,
+                error: trace.error.clone(),
+                chain_id: chain_id,
+                removed: false,
+            })
+        })
+        .collect()
+}
+
+fn call_type_name(call_type: CallType) -> Option<&'static str> {
+    match call_type {
+        CallType::Call => Some("call"),
+        CallType::CallCode => Some("callcode"),
+        CallType::DelegateCall => Some("delegatecall"),
+        CallType::StaticCall => Some("staticcall"),
+        CallType::AuthCall => Some("authcall"),
+        CallType::None => None,
+    }
+}
+
+// Value moved by transactions and their internal calls that took effect, i.e. neither the call
+// nor one of its parents reverted, plus gas fees, withdrawals and block rewards. Transactions are
+// only covered when the block was traced.
+pub fn process_eth_transfers_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    receipts: &Vec<Receipt>,
+    context: &BlockContext,
+    blob_gas_price: Option<u128>,
+    options: &TransformOptions,
+    chain_id: u64
+) -> Vec<EmitterEthTransfer> {
+    let mut transfers: Vec<EmitterEthTransfer> = Vec::new();
+
+    let mut push_transfer = |
+        transaction: Option<(usize, FixedBytes<32>)>,
+        trace_address: Option<&Vec<usize>>,
+        from_address: Option<Address>,
+        to_address: Option<Address>,
+        value: U256,
+        kind: &str
+    | {
+        if value.is_zero() {
+            return;
+        }
+
+        transfers.push(EmitterEthTransfer {
+            block_number: block.number,
+            block_hash: block.hash(),
+            transfer_index: transfers.len() as u64,
+            transaction_index: transaction.map(|(tx_index, _)| tx_index as u64),
+            transaction_hash: transaction.map(|(_, tx_hash)| tx_hash),
+            trace_address: trace_address.map(|trace_address| {
+                trace_address
+                    .iter()
+                    .map(|index| *index as u64)
+                    .collect()
+            }),
+            from_address,
+            to_address,
+            value: options.quantity(value),
+            kind: kind.to_string(),
+            chain_id: chain_id,
+            removed: false,
+        });
+    };
+
+    // transactions + internal calls
+    for (tx_index, transaction_traces) in context.traces.into_iter().flatten().enumerate() {
+        let tx_hash = *block.body().transactions[tx_index].hash();
+
+        // the effects of a failed call and all its sub-calls are reverted
+        let failed_calls: Vec<&Vec<usize>> = transaction_traces
+            .iter()
+            .filter(|trace| trace.error.is_some())
+            .map(|trace| &trace.trace_address)
+            .collect();
+
+        for trace in transaction_traces {
+            if failed_calls.iter().any(|failed_call| trace.trace_address.starts_with(failed_call)) {
+                continue;
+            }
+
+            let kind = match trace.trace_address.is_empty() {
+                true => "top-level",
+                false => "internal",
+            };
+            let transaction = Some((tx_index, tx_hash));
+            let trace_address = Some(&trace.trace_address);
+
+            match (&trace.action, &trace.result) {
+                // delegatecall and callcode don't move value to another account
+                (Action::Call(call), _) if call.call_type == CallType::Call => {
+                    push_transfer(transaction, trace_address, Some(call.from), Some(call.to), call.value, kind);
+                }
+                (Action::Create(create), Some(TraceOutput::Create(output))) => {
+                    push_transfer(
+                        transaction,
+                        trace_address,
+                        Some(create.from),
+                        Some(output.address),
+                        create.value,
+                        kind
+                    );
+                }
+                (Action::Selfdestruct(selfdestruct), _) => {
+                    push_transfer(
+                        transaction,
+                        trace_address,
+                        Some(selfdestruct.address),
+                        Some(selfdestruct.refund_address),
+                        selfdestruct.balance,
+                        "selfdestruct"
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // gas fees, failed transactions pay them as well. The priority fee goes to the beneficiary,
+    // the base fee and the blob fee are burned.
+    let base_fee = block.base_fee_per_gas.unwrap_or_default();
+    for (tx_index, (sender, transaction)) in block.transactions_with_sender().enumerate() {
+        let gas_used = match tx_index {
+            0 => receipts[tx_index].cumulative_gas_used,
+            _ => receipts[tx_index].cumulative_gas_used - receipts[tx_index - 1].cumulative_gas_used,
+        };
+        let priority_fee = transaction
+            .effective_gas_price(block.base_fee_per_gas)
+            .saturating_sub(u128::from(base_fee));
+        let blob_fee = transaction
+            .blob_versioned_hashes()
+            .zip(blob_gas_price)
+            .map(|(hashes, blob_gas_price)| {
+                U256::from((hashes.len() as u64) * DATA_GAS_PER_BLOB) * U256::from(blob_gas_price)
+            })
+            .unwrap_or_default();
+
+        let tip = U256::from(gas_used) * U256::from(priority_fee);
+        let burned = U256::from(gas_used) * U256::from(base_fee) + blob_fee;
+
+        let fee_transaction = Some((tx_index, *transaction.hash()));
+        push_transfer(fee_transaction, None, Some(*sender), Some(block.beneficiary), tip, "fee");
+        push_transfer(fee_transaction, None, Some(*sender), None, burned, "fee");
+    }
+
+    // withdrawals, amounts are in gwei
+    for withdrawal in block.body().withdrawals.iter().flat_map(|withdrawals| withdrawals.iter()) {
+        let value = U256::from(withdrawal.amount) * U256::from(1_000_000_000u64);
+        push_transfer(None, None, None, Some(withdrawal.address), value, "withdrawal");
+    }
+
+    // block and uncle rewards before the merge
+    if let Some(base_block_reward) = context.base_block_reward {
+        let ommers = &block.body().ommers;
+        let reward = block_reward(base_block_reward, ommers.len());
+        push_transfer(None, None, None, Some(block.beneficiary), U256::from(reward), "block-reward");
+
+        for ommer in ommers {
+            let reward = ommer_reward(base_block_reward, block.number, ommer.number);
+            push_transfer(None, None, None, Some(ommer.beneficiary), U256::from(reward), "block-reward");
+        }
+    }
+
+    transfers
+}
+
+// withdrawals are part of the block body since Shanghai
+pub fn process_withdrawals_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    chain_id: u64
+) -> Vec<EmitterWithdrawal> {
+    block
+        .body()
+        .withdrawals.as_ref()
+        .map(|withdrawals| {
+            withdrawals
+                .iter()
+                .enumerate()
+                .map(|(withdrawal_index, withdrawal)| EmitterWithdrawal {
+                    block_number: block.number,
+                    block_hash: block.hash(),
+                    timestamp: block.timestamp(),
+                    withdrawal_index: withdrawal_index as u64,
+                    index: withdrawal.index,
+                    validator_index: withdrawal.validator_index,
+                    address: withdrawal.address,
+                    amount: withdrawal.amount,
+                    chain_id: chain_id,
+                    removed: false,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn process_el_requests_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    requests: &Requests,
+    chain_id: u64
+) -> Vec<EmitterElRequest> {
+    decode_requests(requests)
+        .into_iter()
+        .enumerate()
+        .map(|(request_index, request)| {
+            let el_request = |request_type: &str, validator_pubkey| EmitterElRequest {
+                block_number: block.number,
+                block_hash: block.hash(),
+                timestamp: block.timestamp(),
+                request_index: request_index as u64,
+                request_type: request_type.to_string(),
+                source_address: None,
+                validator_pubkey,
+                target_pubkey: None,
+                withdrawal_credentials: None,
+                signature: None,
+                deposit_index: None,
+                amount: None,
+                chain_id: chain_id,
+                removed: false,
+            };
+
+            match request {
+                ElRequest::Deposit { pubkey, withdrawal_credentials, amount, signature, index } => {
+                    EmitterElRequest {
+                        withdrawal_credentials: Some(withdrawal_credentials),
+                        signature: Some(signature),
+                        deposit_index: Some(index),
+                        amount: Some(amount),
+                        ..el_request("deposit", pubkey)
+                    }
+                }
+                ElRequest::Withdrawal { source_address, validator_pubkey, amount } => {
+                    EmitterElRequest {
+                        source_address: Some(source_address),
+                        amount: Some(amount),
+                        ..el_request("withdrawal", validator_pubkey)
+                    }
+                }
+                ElRequest::Consolidation { source_address, source_pubkey, target_pubkey } => {
+                    EmitterElRequest {
+                        source_address: Some(source_address),
+                        target_pubkey: Some(target_pubkey),
+                        ..el_request("consolidation", source_pubkey)
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+// accounts whose balance changed in the block, e.g. by transfers, fees, rewards or withdrawals
+pub fn process_balance_diffs_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    state_changes: &BlockStateChanges,
+    options: &TransformOptions,
+    chain_id: u64
+) -> Vec<EmitterBalanceDiff> {
+    state_changes.accounts
+        .iter()
+        .filter_map(|change| {
+            let previous_balance = change.previous.as_ref().map(|info| info.balance).unwrap_or_default();
+            let new_balance = change.new.as_ref().map(|info| info.balance).unwrap_or_default();
+
+            if previous_balance == new_balance {
+                return None;
+            }
+
+            Some(EmitterBalanceDiff {
+                block_number: block.number,
+                block_hash: block.hash(),
+                address: change.address,
+                previous_balance: options.quantity(previous_balance),
+                new_balance: options.quantity(new_balance),
+                chain_id: chain_id,
+                removed: false,
+            })
+        })
+        .collect()
+}
+
+// accounts whose nonce changed in the block, by transactions, contract creations or EIP-7702 authorizations
+pub fn process_nonce_diffs_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    state_changes: &BlockStateChanges,
+    chain_id: u64
+) -> Vec<EmitterNonceDiff> {
+    state_changes.accounts
+        .iter()
+        .filter_map(|change| {
+            let previous_nonce = change.previous.as_ref().map(|info| info.nonce).unwrap_or_default();
+            let new_nonce = change.new.as_ref().map(|info| info.nonce).unwrap_or_default();
+
+            if previous_nonce == new_nonce {
+                return None;
+            }
+
+            Some(EmitterNonceDiff {
+                block_number: block.number,
+                block_hash: block.hash(),
+                address: change.address,
+                previous_nonce,
+                new_nonce,
+                chain_id: chain_id,
+                removed: false,
+            })
+        })
+        .collect()
+}
+
+// every storage slot that changed in the block
+pub fn process_storage_diffs_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    state_changes: &BlockStateChanges,
+    chain_id: u64
+) -> Vec<EmitterStorageDiff> {
+    state_changes.storage
+        .iter()
+        .map(|change| EmitterStorageDiff {
+            block_number: block.number,
+            block_hash: block.hash(),
+            address: change.address,
+            slot: FixedBytes::from(change.slot),
+            previous_value: FixedBytes::from(change.previous),
+            new_value: FixedBytes::from(change.new),
+            chain_id: chain_id,
+            removed: false,
+        })
+        .collect()
+}
+
+// Contracts are accounts that had no code before the block and have code after it. This covers
+// deployments by internal calls as well, which are not visible in the transactions.
+pub fn process_contracts_in_block(
+    block: &reth::primitives::RecoveredBlock<alloy::consensus::Block<TransactionSigned>>,
+    state_changes: &BlockStateChanges,
+    emitter_transactions: &Vec<EmitterTransactionRecords>,
+    traces: Option<&Vec<Vec<TransactionTrace>>>,
+    chain_id: u64
+) -> Vec<EmitterContract> {
+    state_changes.accounts
+        .iter()
+        .filter_map(|change| {
+            let new = change.new.as_ref()?;
+            let had_code = change.previous
+                .as_ref()
+                .is_some_and(|previous| previous.code_hash != KECCAK_EMPTY);
+            // EIP-7702 delegations set code on EOAs, but don't deploy a contract
+            let is_delegation = new.code.as_ref().is_some_and(|code| code.is_eip7702());
+
+            if had_code || is_delegation || new.code_hash == KECCAK_EMPTY {
+                return None;
+            }
+
+            let creation_transaction = emitter_transactions
+                .iter()
+                .map(|transaction_records| &transaction_records.transaction)
+                .find(|emitter_transaction| emitter_transaction.contract_address == Some(change.address))
+                .map(|transaction| (transaction.from_address, transaction.transaction_index, transaction.transaction_hash));
+
+            // internal deployments, from the create trace whose output is the contract
+            let creation = creation_transaction.or_else(|| {
+                traces?
+                    .iter()
+                    .enumerate()
+                    .find_map(|(tx_index, transaction_traces)| {
+                        transaction_traces.iter().find_map(|trace| {
+                            let (Action::Create(create), Some(TraceOutput::Create(output))) = (&trace.action, &trace.result) else {
+                                return None;
+                            };
+                            let tx_hash = *block.body().transactions[tx_index].hash();
+                            (output.address == change.address).then_some((create.from, tx_index as u64, tx_hash))
+                        })
+                    })
+            });
+
+            Some(EmitterContract {
+                block_number: block.number,
+                block_hash: block.hash(),
+                contract_address: change.address,
+                deployer: creation.map(|(deployer, _, _)| deployer),
+                transaction_index: creation.map(|(_, tx_index, _)| tx_index),
+                transaction_hash: creation.map(|(_, _, tx_hash)| tx_hash),
+                code_hash: new.code_hash,
+                chain_id: chain_id,
+                removed: false,
+            })
+        })
+        .collect()
+}